@@ -1,10 +1,9 @@
 use std::env::current_dir;
 use std::fs::create_dir_all;
 
-use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
+use cosmwasm_schema::remove_schemas;
 
-use dao::msg::*;
-use dao::state::*;
+use dao::schema::export_schemas;
 
 fn main() {
     let mut out_dir = current_dir().unwrap();
@@ -12,10 +11,5 @@ fn main() {
     create_dir_all(&out_dir).unwrap();
     remove_schemas(&out_dir).unwrap();
 
-    export_schema(&schema_for!(InstantiateMsg), &out_dir);
-    export_schema(&schema_for!(ExecuteMsg), &out_dir);
-    export_schema(&schema_for!(QueryMsg), &out_dir);
-    export_schema(&schema_for!(State), &out_dir);
-    export_schema(&schema_for!(Proposal), &out_dir);
-    export_schema(&schema_for!(DaoItem), &out_dir);
+    export_schemas(&out_dir);
 }
@@ -1,13 +1,31 @@
-use crate::msg::{self, ProposeMsg, VoteMsg, VotePosition, WithdrawVoteMsg};
-use crate::state::{Proposal, ProposalStatus, ITEMS, PROPOSALS, STATE};
+use crate::choice;
+use crate::delegation;
+use crate::events;
+use crate::msg::{
+    self, DepositForfeit, DepositRefund, ItemListResponse, ItemResponse, ProposalListResponse,
+    ProposalResponse, ProposalTally, ProposeMsg, VoteMsg, VotePosition, VoteResponse,
+    VoterVoteListResponse, VoterVoteResponse, WithdrawVoteMsg,
+};
+use crate::pause;
+use crate::state::{
+    proposals, Ballot, Proposal, ProposalStatus, ITEMS, PROPOSAL_COUNT, STAKED, STATE, TOKEN_INFO,
+    VOTERS,
+};
 use crate::tokens;
+use crate::treasury;
+use crate::vesting;
 use crate::ContractError;
-use cosmwasm_std::{DepsMut, Env, MessageInfo, Response, StdError, Uint128};
+use cosmwasm_std::{
+    BankMsg, BlockInfo, Coin, Decimal, Deps, DepsMut, Empty, Env, MessageInfo, Order, Response,
+    StdError, StdResult, Storage, SubMsg, Uint128,
+};
+use cw0::{must_pay, PaymentError};
+use cw_storage_plus::{Bound, U64Key};
 use msg::ProposeAction;
 use std::cmp::Ordering;
 
 pub(crate) fn handle_propose(
-    mut deps: DepsMut,
+    deps: DepsMut,
     env: Env,
     info: MessageInfo,
     proposal: ProposeMsg,
@@ -15,205 +33,937 @@ pub(crate) fn handle_propose(
     let state = STATE.load(deps.storage)?;
     let cost = state.proposal_cost;
 
-    let contract_addr = env.contract.address.to_string();
-    // Transfer the proposal cost to this contract. If this fails the
-    // program will bail out.
-    tokens::execute_transfer(deps.branch(), env, info.clone(), contract_addr, cost)?;
+    pause::assert_not_paused(deps.storage, Some(&proposal.action))?;
+    validate_action(deps.as_ref(), &proposal.action)?;
+
+    let proposal_id = PROPOSAL_COUNT.load(deps.storage)?;
 
-    let proposals =
-        PROPOSALS.update(deps.storage, |mut proposals| -> Result<_, ContractError> {
-            proposals.push(Proposal::new(proposal.clone(), info.sender, cost));
-            Ok(proposals)
-        })?;
+    // Proposals may be paid for with native coins if the DAO allows
+    // it. Otherwise lock the proposal cost. If the proposer doesn't
+    // have enough unlocked tokens the program will bail out.
+    let (cost, native_deposit) = if info.funds.is_empty() {
+        if !cost.is_zero() {
+            vesting::assert_unlocked(deps.storage, &env.block, &info.sender, cost)?;
+            tokens::stake(deps.storage, &info.sender, proposal_id, cost)?;
+        }
+        (cost, None)
+    } else {
+        let native_cost = state
+            .native_proposal_cost
+            .ok_or(PaymentError::NonPayable {})?;
+        let paid = must_pay(&info, &native_cost.denom)?;
+        if paid < native_cost.amount {
+            return Err(ContractError::InsufficentProposalFunds {
+                needed: native_cost.amount,
+                got: paid,
+            });
+        }
+        let deposit = Coin {
+            denom: native_cost.denom,
+            amount: paid,
+        };
+        treasury::lock_native_deposit(deps.storage, &deposit)?;
+        (Uint128::zero(), Some(deposit))
+    };
+
+    let mut new = Proposal::new(
+        proposal_id,
+        proposal.clone(),
+        info.sender,
+        cost,
+        native_deposit,
+        state.quadratic,
+        &env.block,
+    );
+    if state.pre_propose.is_some() {
+        new.status = ProposalStatus::PendingApproval;
+    }
+    proposals().save(deps.storage, U64Key::new(proposal_id), &new)?;
+    PROPOSAL_COUNT.save(deps.storage, &(proposal_id + 1))?;
 
     Ok(Response::new()
+        .add_event(events::proposal_created(proposal_id, &new))
         .add_attribute("method", "propose")
         .add_attribute("title", proposal.title)
         .add_attribute("body", proposal.body)
-        .add_attribute("proposal_id", proposals.len().to_string())
-        .add_attribute("action", format!("{:?}", proposal.action)))
+        .add_attribute("proposal_id", proposal_id.to_string()))
+}
+
+/// Catches actions that could never be executed before they are put
+/// to a vote.
+fn validate_action(deps: Deps, action: &ProposeAction) -> Result<(), ContractError> {
+    match action {
+        ProposeAction::TreasuryTransfer {
+            recipient: addr,
+            amount,
+        }
+        | ProposeAction::TreasurySend {
+            contract: addr,
+            amount,
+            ..
+        } => {
+            deps.api.addr_validate(addr)?;
+            if amount.is_zero() {
+                return Err(ContractError::InvalidZeroAmount);
+            }
+        }
+        ProposeAction::CreateVesting { address, schedule } => {
+            deps.api.addr_validate(address)?;
+            schedule.validate()?;
+        }
+        ProposeAction::BankSend { to, amount } => {
+            deps.api.addr_validate(to)?;
+            if amount.is_empty() || amount.iter().any(|c| c.amount.is_zero()) {
+                return Err(ContractError::InvalidZeroAmount);
+            }
+        }
+        ProposeAction::UpdateConfig(update) => {
+            update.validate(TOKEN_INFO.load(deps.storage)?.total_supply)?;
+        }
+        ProposeAction::ChangeGuardian {
+            guardian: Some(guardian),
+        } => {
+            deps.api.addr_validate(guardian)?;
+        }
+        ProposeAction::UpdateCw20Tokens { add, remove } => {
+            for addr in add.iter().chain(remove.iter()) {
+                deps.api.addr_validate(addr)?;
+            }
+        }
+        ProposeAction::ChangeCancelPolicy { policy } if policy.fee > Decimal::one() => {
+            return Err(ContractError::InvalidCancelFee);
+        }
+        ProposeAction::MultipleChoice { choices } => {
+            if choices.len() < 2 {
+                return Err(ContractError::InvalidChoice {
+                    reason: "at least two choices are needed".to_string(),
+                });
+            }
+            for action in choices.iter().filter_map(|c| c.action.as_ref()) {
+                if let ProposeAction::MultipleChoice { .. } = action {
+                    return Err(ContractError::InvalidChoice {
+                        reason: "choices can not be nested".to_string(),
+                    });
+                }
+                validate_action(deps, action)?;
+            }
+        }
+        _ => (),
+    }
+    Ok(())
 }
 
 pub(crate) fn handle_vote(
-    mut deps: DepsMut,
+    deps: DepsMut,
     env: Env,
     info: MessageInfo,
     vote: VoteMsg,
+) -> Result<Response, ContractError> {
+    let options = [(vote.position, Decimal::one())];
+    cast_vote(
+        deps,
+        env,
+        info,
+        vote.proposal_id,
+        vote.amount,
+        &options,
+        vote.ranking,
+    )
+}
+
+/// Stakes `amount` tokens on a proposal, split between positions
+/// according to the weights in `options`.
+pub(crate) fn handle_vote_weighted(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    proposal_id: u64,
+    amount: Uint128,
+    options: Vec<(VotePosition, Decimal)>,
+) -> Result<Response, ContractError> {
+    validate_weights(&options)?;
+    cast_vote(deps, env, info, proposal_id, amount, &options, vec![])
+}
+
+/// Errors unless the weights are positive, for distinct positions,
+/// and add up to one. Weights above one are turned away before they
+/// are summed so that the sum can't overflow.
+fn validate_weights(options: &[(VotePosition, Decimal)]) -> Result<(), ContractError> {
+    let mut total = Decimal::zero();
+    for (i, (position, weight)) in options.iter().enumerate() {
+        if weight.is_zero()
+            || *weight > Decimal::one()
+            || options[..i].iter().any(|(p, _)| p == position)
+        {
+            return Err(ContractError::InvalidVoteWeights);
+        }
+        total = total + *weight;
+    }
+    if total != Decimal::one() {
+        return Err(ContractError::InvalidVoteWeights);
+    }
+    Ok(())
+}
+
+/// Splits `amount` between positions by weight. Any remainder left
+/// over from rounding goes to the first position so that the parts
+/// always add up to `amount`.
+pub(crate) fn split_vote(
+    amount: Uint128,
+    options: &[(VotePosition, Decimal)],
+) -> Vec<(VotePosition, Uint128)> {
+    let mut parts: Vec<_> = options
+        .iter()
+        .map(|(position, weight)| (position.clone(), amount * *weight))
+        .collect();
+    let assigned: Uint128 = parts.iter().map(|(_, part)| *part).sum();
+    if let Some((_, first)) = parts.first_mut() {
+        *first += amount - assigned;
+    }
+    parts.retain(|(_, part)| !part.is_zero());
+    parts
+}
+
+fn cast_vote(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    proposal_id: u64,
+    amount: Uint128,
+    options: &[(VotePosition, Decimal)],
+    ranking: Vec<u32>,
 ) -> Result<Response, ContractError> {
     let state = STATE.load(deps.storage)?;
 
-    let contract_addr = env.contract.address.as_str().to_string();
-    // Transfer the vote stake amount to this contract. If this fails the
-    // program will bail out. This will fail if amount is zero.
-    tokens::execute_transfer(
-        deps.branch(),
-        env.clone(),
-        info.clone(),
-        contract_addr,
-        vote.amount,
-    )?;
+    let mut proposal = load_proposal(deps.storage, proposal_id)?;
+    match proposal.status {
+        ProposalStatus::Pending => (),
+        ProposalStatus::PendingApproval => return Err(ContractError::ProposalNotApproved),
+        _ => return Err(ContractError::VoteOnCompletedProposal),
+    }
+    pause::assert_not_paused(deps.storage, Some(&proposal.action))?;
 
-    // Need to do this because the compiler doesn't want to capture
-    // vote by reference in the closure below for some reason.
-    let proposal_id = vote.proposal_id;
-    let amount = vote.amount;
-
-    let proposals =
-        PROPOSALS.update(deps.storage, |mut proposals| -> Result<_, ContractError> {
-            match proposals.get_mut(vote.proposal_id as usize) {
-                Some(proposal) => {
-                    if proposal.status != ProposalStatus::Pending {
-                        return Err(ContractError::VoteOnCompletedProposal);
-                    }
-
-                    proposal.add_vote(&info.sender, vote.position, vote.amount);
-
-                    let staked = proposal.get_total_votes();
-                    if staked >= state.quorum {
-                        match proposal
-                            .get_votes(VotePosition::Yes)
-                            .cmp(&proposal.get_votes(VotePosition::No))
-                        {
-                            Ordering::Less | Ordering::Equal => {
-                                proposal.status = ProposalStatus::Failed
-                            }
-                            Ordering::Greater => proposal.status = ProposalStatus::Passed,
-                        };
-                    }
-                }
-                None => {
-                    return Err(ContractError::Std(StdError::NotFound {
-                        kind: format!("no such proposal ID ({})", vote.proposal_id),
-                    }))
-                }
-            }
-            Ok(proposals)
-        })?;
+    // Lock the vote stake amount. If this fails the program will
+    // bail out. Delegates may vote with only the power delegated to
+    // them in which case nothing is staked.
+    let direct = !amount.is_zero();
+    if direct {
+        if !state.locked_tokens_can_vote {
+            vesting::assert_unlocked(deps.storage, &env.block, &info.sender, amount)?;
+        }
+        tokens::stake(deps.storage, &info.sender, proposal_id, amount)?;
+    }
+
+    for (position, part) in split_vote(amount, options) {
+        choice::validate_ranking(&proposal, &position, &ranking)?;
+        if position == VotePosition::Yes && !ranking.is_empty() {
+            proposal.ballots.push(Ballot {
+                voter: info.sender.clone(),
+                amount: part,
+                ranking: ranking.clone(),
+            });
+        }
+        proposal.add_vote(&info.sender, position, part);
+    }
+    if direct {
+        VOTERS.save(
+            deps.storage,
+            (&info.sender, U64Key::new(proposal_id)),
+            &Empty {},
+        )?;
+        // Voting directly overrides any vote that the sender's
+        // delegate has made on their behalf.
+        proposal.remove_delegated_votes(&info.sender);
+    }
+    let delegated = proposal.delegated.len();
+    delegation::cast_delegated_votes(
+        deps.storage,
+        &env.block,
+        &mut proposal,
+        &info.sender,
+        options,
+    )?;
+    if !direct && proposal.delegated.len() == delegated {
+        return Err(ContractError::InvalidZeroAmount);
+    }
+    delegation::recheck_delegated_votes(deps.storage, &env.block, &mut proposal)?;
 
-    if proposals[proposal_id as usize].status != ProposalStatus::Pending {
-        handle_proposal_completion(deps, env, info, &proposals[proposal_id as usize])?;
+    let staked = proposal.get_total_votes();
+    if staked >= state.quorum && choice::choice_count(&proposal).is_some() {
+        proposal.winner = choice::instant_runoff(&proposal)?;
+        proposal.status = match proposal.winner {
+            Some(_) => ProposalStatus::Passed,
+            None => ProposalStatus::Failed,
+        };
+    } else if staked >= state.quorum {
+        // Vetoes count as no votes.
+        let no = proposal
+            .get_weighted_votes(VotePosition::No)?
+            .checked_add(proposal.get_weighted_votes(VotePosition::NoWithVeto)?)
+            .map_err(StdError::from)?;
+        match proposal.get_weighted_votes(VotePosition::Yes)?.cmp(&no) {
+            Ordering::Less | Ordering::Equal => proposal.status = ProposalStatus::Failed,
+            Ordering::Greater => proposal.status = ProposalStatus::Passed,
+        };
+        if proposal.is_vetoed(state.veto_threshold)? {
+            proposal.status = ProposalStatus::Failed;
+        }
+    }
+    // Give members time to react before a passed proposal takes
+    // effect.
+    if let (ProposalStatus::Passed, Some(timelock)) = (&proposal.status, state.timelock) {
+        proposal.status = ProposalStatus::Queued {
+            executable_at: timelock.after(&env.block),
+        };
     }
+    if proposal.status != ProposalStatus::Pending {
+        proposal.completed = Some((&env.block).into());
+    }
+    proposals().save(deps.storage, U64Key::new(proposal_id), &proposal)?;
 
-    Ok(Response::new()
+    let votes = split_vote(amount, options)
+        .into_iter()
+        .map(|(position, part)| {
+            events::vote_cast(proposal_id, &info.sender, &position, part, &proposal.status)
+        });
+    let res = Response::new().add_events(votes);
+    let res = if proposal.status != ProposalStatus::Pending {
+        let completion = handle_proposal_completion(deps, env, proposal_id, &proposal)?;
+        res.add_submessages(completion.messages)
+            .add_events(completion.events)
+    } else {
+        res
+    };
+
+    Ok(res
         .add_attribute("method", "vote")
         .add_attribute("proposal_id", proposal_id.to_string())
         .add_attribute("tokens", amount))
 }
 
-/// On proposal completion the submitter of the proposal and all of
-/// the voters ought to have their tokens returned.
+/// On proposal completion the submitter of the proposal has their
+/// deposit settled. Voters claim their stakes back separately.
+/// Returns the messages and events that completing the proposal and
+/// executing its action produced.
 fn handle_proposal_completion(
     mut deps: DepsMut,
     env: Env,
-    _info: MessageInfo,
+    proposal_id: u64,
     proposal: &Proposal,
-) -> Result<(), ContractError> {
+) -> Result<Response, ContractError> {
     assert!(proposal.status != ProposalStatus::Pending);
 
-    // Refund the proposer.
-    tokens::execute_transfer(
-        deps.branch(),
-        env.clone(),
-        MessageInfo {
-            sender: env.contract.address.clone(),
-            funds: vec![],
-        },
-        proposal.proposer.to_string(),
-        proposal.proposal_cost,
-    )?;
-
-    // Refund the voters.
-    for (addr, amount) in proposal
-        .yes
-        .iter()
-        .chain(proposal.no.iter().chain(proposal.abstain.iter()))
-    {
-        tokens::execute_transfer(
+    let state = STATE.load(deps.storage)?;
+    // A vetoed proposal always loses its deposit.
+    let refund = !proposal.is_vetoed(state.veto_threshold)?
+        && match state.deposit_policy.refund {
+            DepositRefund::Always => true,
+            DepositRefund::OnPass => matches!(
+                proposal.status,
+                ProposalStatus::Passed | ProposalStatus::Queued { .. }
+            ),
+            DepositRefund::OnQuorum => {
+                proposal
+                    .get_votes(VotePosition::Yes)
+                    .checked_add(proposal.get_votes(VotePosition::No))
+                    .map_err(StdError::from)?
+                    >= state.quorum
+            }
+        };
+    let messages = if refund {
+        refund_deposit(deps.branch(), proposal_id, proposal)?
+    } else {
+        forfeit_deposit(
             deps.branch(),
             env.clone(),
-            MessageInfo {
-                sender: env.contract.address.clone(),
-                funds: vec![],
-            },
-            addr.to_string(),
-            *amount,
-        )?;
-    }
+            &state.deposit_policy.forfeit,
+            proposal_id,
+            proposal,
+        )?
+    };
+
+    // Voters claim their stakes back themselves. See `refunds`.
+    let res = Response::new()
+        .add_submessages(messages)
+        .add_event(events::proposal_completed(proposal_id, proposal));
 
-    // Perform an action as needed.
+    // Perform an action as needed. Queued proposals are executed
+    // by `handle_execute_proposal` once their timelock is up.
     if proposal.status != ProposalStatus::Passed {
-	return Ok(())
+        return Ok(res);
     }
+    let executed = execute_passed(deps, env, proposal)?;
+    Ok(res
+        .add_submessages(executed.messages)
+        .add_events(executed.events))
+}
+
+/// Executes the action of a passed proposal, or of the winning
+/// choice for multiple choice proposals.
+fn execute_passed(deps: DepsMut, env: Env, proposal: &Proposal) -> Result<Response, ContractError> {
+    let action = match (&proposal.action, proposal.winner) {
+        (ProposeAction::MultipleChoice { choices }, Some(winner)) => {
+            choices[winner as usize].action.as_ref()
+        }
+        (action, _) => Some(action),
+    };
+    match action {
+        Some(action) => execute_action(deps, env, action),
+        None => Ok(Response::new()),
+    }
+}
+
+pub(crate) fn handle_execute_proposal(
+    deps: DepsMut,
+    env: Env,
+    _info: MessageInfo,
+    proposal_id: u64,
+) -> Result<Response, ContractError> {
+    let mut proposal = load_proposal(deps.storage, proposal_id)?;
+    match &proposal.status {
+        ProposalStatus::Queued { executable_at } if executable_at.is_expired(&env.block) => (),
+        ProposalStatus::Queued { executable_at } => {
+            return Err(ContractError::TimelockNotElapsed {
+                executable_at: executable_at.to_string(),
+            })
+        }
+        _ => return Err(ContractError::ProposalNotQueued),
+    }
+    pause::assert_not_paused(deps.storage, Some(&proposal.action))?;
+    proposal.status = ProposalStatus::Passed;
+    proposals().save(deps.storage, U64Key::new(proposal_id), &proposal)?;
+
+    let executed = execute_passed(deps, env, &proposal)?;
+    Ok(Response::new()
+        .add_event(events::proposal_status_changed(
+            proposal_id,
+            &proposal.status,
+        ))
+        .add_submessages(executed.messages)
+        .add_events(executed.events)
+        .add_attributes(executed.attributes)
+        .add_attribute("method", "execute_proposal")
+        .add_attribute("proposal_id", proposal_id.to_string()))
+}
 
-    match &proposal.action {
+/// Carries out the action of a passed proposal.
+fn execute_action(
+    mut deps: DepsMut,
+    env: Env,
+    action: &ProposeAction,
+) -> Result<Response, ContractError> {
+    let mut res = Response::new();
+    match action {
         ProposeAction::ChangeQuorum { new_quorum } => {
             STATE.update(deps.storage, |mut state| -> Result<_, ContractError> {
                 state.quorum = *new_quorum;
                 Ok(state)
             })?;
+            res = res.add_event(events::config_changed("quorum", new_quorum)?);
         }
         ProposeAction::ChangeProposalCost { new_proposal_cost } => {
             STATE.update(deps.storage, |mut state| -> Result<_, ContractError> {
                 state.proposal_cost = *new_proposal_cost;
                 Ok(state)
             })?;
+            res = res.add_event(events::config_changed("proposal_cost", new_proposal_cost)?);
         }
         ProposeAction::AddItem(item) => {
-            ITEMS.update(deps.storage, |mut items| -> Result<_, ContractError> {
+            let items = ITEMS.update(deps.storage, |mut items| -> Result<_, ContractError> {
                 items.push(item.clone());
                 Ok(items)
             })?;
+            res = res.add_event(events::item_added(items.len() as u64 - 1, item));
         }
         ProposeAction::RemoveItem { id } => {
             ITEMS.update(deps.storage, |mut items| -> Result<_, ContractError> {
                 items.remove(*id as usize);
                 Ok(items)
             })?;
+            res = res.add_event(events::item_removed(*id));
+        }
+        ProposeAction::UpdateConfig(update) => {
+            // The supply may have changed since the update was proposed.
+            update.validate(TOKEN_INFO.load(deps.storage)?.total_supply)?;
+            let mut state = STATE.load(deps.storage)?;
+            let changed = update.apply(&mut state)?;
+            STATE.save(deps.storage, &state)?;
+            res = res.add_events(changed);
+        }
+        ProposeAction::ChangeDepositPolicy { policy } => {
+            STATE.update(deps.storage, |mut state| -> Result<_, ContractError> {
+                state.deposit_policy = policy.clone();
+                Ok(state)
+            })?;
+            res = res.add_event(events::config_changed("deposit_policy", policy)?);
+        }
+        ProposeAction::ChangeCancelPolicy { policy } => {
+            STATE.update(deps.storage, |mut state| -> Result<_, ContractError> {
+                state.cancel_policy = policy.clone();
+                Ok(state)
+            })?;
+            res = res.add_event(events::config_changed("cancel_policy", policy)?);
         }
+        ProposeAction::ChangeTokenMode { mode } => {
+            TOKEN_INFO.update(deps.storage, |mut info| -> Result<_, ContractError> {
+                info.mode = mode.clone();
+                Ok(info)
+            })?;
+            res = res.add_event(events::config_changed("token_mode", mode)?);
+        }
+        ProposeAction::CreateVesting { address, schedule } => {
+            let address = deps.api.addr_validate(address)?;
+            vesting::grant_vesting(deps.branch(), &address, schedule.clone())?;
+        }
+        ProposeAction::TreasuryTransfer { recipient, amount } => {
+            treasury::transfer(deps.branch(), env, recipient.clone(), *amount)?;
+        }
+        ProposeAction::Unpause => {
+            STATE.update(deps.storage, |mut state| -> Result<_, ContractError> {
+                state.paused = false;
+                state.transfers_paused = false;
+                Ok(state)
+            })?;
+            res = res.add_event(events::config_changed("paused", &false)?);
+        }
+        ProposeAction::ChangeGuardian { guardian } => {
+            let guardian = guardian
+                .as_ref()
+                .map(|g| deps.api.addr_validate(g))
+                .transpose()?;
+            res = res.add_event(events::config_changed("guardian", &guardian)?);
+            STATE.update(deps.storage, |mut state| -> Result<_, ContractError> {
+                state.guardian = guardian;
+                Ok(state)
+            })?;
+        }
+        ProposeAction::UpdateCw20Tokens { add, remove } => {
+            treasury::update_cw20_tokens(deps.branch(), add, remove)?;
+            let tokens = treasury::query_cw20_tokens(deps.as_ref())?.tokens;
+            res = res.add_event(events::config_changed("cw20_tokens", &tokens)?);
+        }
+        ProposeAction::BankSend { to, amount } => {
+            res = res.add_submessage(treasury::bank_send(
+                deps.as_ref(),
+                &env,
+                to.clone(),
+                amount.clone(),
+            )?);
+        }
+        ProposeAction::TreasurySend {
+            contract,
+            amount,
+            msg,
+        } => {
+            let sent = treasury::send(deps.branch(), env, contract.clone(), *amount, msg.clone())?;
+            res = res.add_submessages(sent.messages);
+        }
+        // Handled by executing the winning choice's action. Nested
+        // choices are rejected when proposing.
+        ProposeAction::MultipleChoice { .. } => (),
     }
 
-    Ok(())
+    Ok(res)
 }
 
-pub(crate) fn handle_withdrawal(
+/// Returns a proposal's deposit to its proposer.
+pub(crate) fn refund_deposit(
+    deps: DepsMut,
+    proposal_id: u64,
+    proposal: &Proposal,
+) -> Result<Vec<SubMsg>, ContractError> {
+    tokens::unstake(
+        deps.storage,
+        &proposal.proposer,
+        proposal_id,
+        proposal.proposal_cost,
+    )?;
+    let mut messages = vec![];
+    if let Some(deposit) = &proposal.native_deposit {
+        messages.push(treasury::refund_native_deposit(
+            deps.storage,
+            proposal.proposer.as_str(),
+            deposit,
+        )?);
+    }
+    Ok(messages)
+}
+
+/// Moves a proposal's deposit into the treasury or burns it.
+fn forfeit_deposit(
     mut deps: DepsMut,
     env: Env,
+    forfeit: &DepositForfeit,
+    proposal_id: u64,
+    proposal: &Proposal,
+) -> Result<Vec<SubMsg>, ContractError> {
+    let cost = proposal.proposal_cost;
+    tokens::forfeit_stake(deps.storage, &env, &proposal.proposer, proposal_id, cost)?;
+
+    let mut messages = vec![];
+    if let Some(deposit) = &proposal.native_deposit {
+        treasury::unlock_native_deposit(deps.storage, deposit)?;
+        if *forfeit == DepositForfeit::Burn {
+            messages.push(SubMsg::new(BankMsg::Burn {
+                amount: vec![deposit.clone()],
+            }));
+        }
+    }
+    if *forfeit == DepositForfeit::Burn && !cost.is_zero() {
+        let info = MessageInfo {
+            sender: env.contract.address.clone(),
+            funds: vec![],
+        };
+        tokens::execute_burn(deps.branch(), env, info, cost)?;
+    }
+    Ok(messages)
+}
+
+/// Cancels a proposal that is being voted on. Voters may claim their
+/// stakes back in full and the proposer gets their deposit back less
+/// the cancellation fee, which is kept by the treasury.
+///
+/// The guardian may still cancel queued proposals while the DAO is
+/// paused but proposers may not cancel until the pause is lifted.
+pub(crate) fn handle_cancel(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    proposal_id: u64,
+) -> Result<Response, ContractError> {
+    let state = STATE.load(deps.storage)?;
+    let policy = state.cancel_policy;
+    let mut proposal = load_proposal(deps.storage, proposal_id)?;
+
+    // Deposits on queued proposals were settled when they passed so
+    // the guardian only needs to stop the action.
+    if let ProposalStatus::Queued { .. } = proposal.status {
+        if state.guardian.as_ref() != Some(&info.sender) {
+            return Err(ContractError::Unauthorized);
+        }
+        proposal.status = ProposalStatus::Cancelled;
+        proposals().save(deps.storage, U64Key::new(proposal_id), &proposal)?;
+        return Ok(Response::new()
+            .add_event(events::proposal_completed(proposal_id, &proposal))
+            .add_attribute("method", "cancel")
+            .add_attribute("proposal_id", proposal_id.to_string())
+            .add_attribute("guardian", info.sender));
+    }
+
+    if proposal.proposer != info.sender {
+        return Err(ContractError::Unauthorized);
+    }
+    if proposal.status != ProposalStatus::Pending {
+        return Err(ContractError::ProposalNotPending);
+    }
+    pause::assert_not_paused(deps.storage, Some(&proposal.action))?;
+    if !policy.after_votes && !proposal.get_total_votes().is_zero() {
+        return Err(ContractError::CancelNotAllowed);
+    }
+
+    let cost = proposal.proposal_cost;
+    let fee = cost * policy.fee;
+    tokens::forfeit_stake(deps.storage, &env, &proposal.proposer, proposal_id, fee)?;
+    tokens::unstake(deps.storage, &proposal.proposer, proposal_id, cost - fee)?;
+
+    let mut messages = vec![];
+    if let Some(deposit) = &proposal.native_deposit {
+        let fee = Coin {
+            denom: deposit.denom.clone(),
+            amount: deposit.amount * policy.fee,
+        };
+        let refund = Coin {
+            denom: deposit.denom.clone(),
+            amount: deposit.amount - fee.amount,
+        };
+        treasury::unlock_native_deposit(deps.storage, &fee)?;
+        if !refund.amount.is_zero() {
+            messages.push(treasury::refund_native_deposit(
+                deps.storage,
+                proposal.proposer.as_str(),
+                &refund,
+            )?);
+        }
+    }
+
+    proposal.status = ProposalStatus::Cancelled;
+    proposal.completed = Some((&env.block).into());
+    proposals().save(deps.storage, U64Key::new(proposal_id), &proposal)?;
+
+    Ok(Response::new()
+        .add_submessages(messages)
+        .add_event(events::proposal_completed(proposal_id, &proposal))
+        .add_attribute("method", "cancel")
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_attribute("fee", fee))
+}
+
+/// Builds the response that queries return for a proposal.
+pub(crate) fn proposal_response(proposal: Proposal, block: &BlockInfo) -> ProposalResponse {
+    let executable = matches!(
+        &proposal.status,
+        ProposalStatus::Queued { executable_at } if executable_at.is_expired(block)
+    );
+    let tally = ProposalTally {
+        yes: proposal.get_votes(VotePosition::Yes),
+        no: proposal.get_votes(VotePosition::No),
+        abstain: proposal.get_votes(VotePosition::Abstain),
+        no_with_veto: proposal.get_votes(VotePosition::NoWithVeto),
+    };
+    ProposalResponse {
+        id: proposal.id,
+        title: proposal.title,
+        body: proposal.body,
+        action: proposal.action,
+        proposer: proposal.proposer,
+        status: proposal.status,
+        executable,
+        tally,
+        winner: proposal.winner,
+        proposal_cost: proposal.proposal_cost,
+        native_deposit: proposal.native_deposit,
+        created: proposal.created,
+        completed: proposal.completed,
+    }
+}
+
+pub(crate) fn load_proposal(storage: &dyn Storage, proposal_id: u64) -> StdResult<Proposal> {
+    proposals()
+        .may_load(storage, U64Key::new(proposal_id))?
+        .ok_or_else(|| StdError::NotFound {
+            kind: format!("no such proposal ID ({})", proposal_id),
+        })
+}
+
+// Settings for pagination.
+pub(crate) const MAX_LIMIT: u32 = 30;
+pub(crate) const DEFAULT_LIMIT: u32 = 10;
+
+/// Collects up to `limit` proposals from a range over proposal IDs.
+fn list_proposals(
+    range: impl Iterator<Item = StdResult<(Vec<u8>, Proposal)>>,
+    limit: Option<u32>,
+    block: &BlockInfo,
+) -> StdResult<ProposalListResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let proposals = range
+        .take(limit)
+        .map(|item| item.map(|(_, p)| proposal_response(p, block)))
+        .collect::<StdResult<_>>()?;
+    Ok(ProposalListResponse { proposals })
+}
+
+pub fn query_proposals(deps: Deps, env: Env) -> StdResult<ProposalListResponse> {
+    let proposals = proposals()
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, p)| proposal_response(p, &env.block)))
+        .collect::<StdResult<_>>()?;
+    Ok(ProposalListResponse { proposals })
+}
+
+pub fn query_proposal(deps: Deps, env: Env, proposal_id: u64) -> StdResult<ProposalResponse> {
+    let proposal = load_proposal(deps.storage, proposal_id)?;
+    Ok(proposal_response(proposal, &env.block))
+}
+
+/// Lists proposals with `status` in order of ID. Queued proposals
+/// match whatever their `executable_at`.
+pub fn query_proposals_by_status(
+    deps: Deps,
+    env: Env,
+    status: ProposalStatus,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<ProposalListResponse> {
+    let start = start_after.map(Bound::exclusive_int);
+    let range = proposals()
+        .idx
+        .status
+        .prefix(events::status_name(&status).as_bytes().to_vec())
+        .range(deps.storage, start, None, Order::Ascending);
+    list_proposals(range, limit, &env.block)
+}
+
+pub fn query_proposals_by_proposer(
+    deps: Deps,
+    env: Env,
+    proposer: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<ProposalListResponse> {
+    let proposer = deps.api.addr_validate(&proposer)?;
+    let start = start_after.map(Bound::exclusive_int);
+    let range = proposals()
+        .idx
+        .proposer
+        .prefix(proposer.as_bytes().to_vec())
+        .range(deps.storage, start, None, Order::Ascending);
+    list_proposals(range, limit, &env.block)
+}
+
+/// Lists proposals newest first.
+pub fn query_reverse_proposals(
+    deps: Deps,
+    env: Env,
+    start_before: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<ProposalListResponse> {
+    let end = start_before.map(Bound::exclusive_int);
+    let range = proposals().range(deps.storage, None, end, Order::Descending);
+    list_proposals(range, limit, &env.block)
+}
+
+pub fn query_items(deps: Deps) -> StdResult<ItemListResponse> {
+    let items = ITEMS
+        .load(deps.storage)?
+        .into_iter()
+        .enumerate()
+        .map(|(id, item)| ItemResponse {
+            id: id as u64,
+            name: item.name,
+            contents: item.contents,
+        })
+        .collect();
+    Ok(ItemListResponse { items })
+}
+
+pub fn query_item(deps: Deps, item_id: u64) -> StdResult<ItemResponse> {
+    query_items(deps)?
+        .items
+        .into_iter()
+        .nth(item_id as usize)
+        .ok_or_else(|| StdError::NotFound {
+            kind: format!("no such item ID ({})", item_id),
+        })
+}
+
+pub fn query_vote(deps: Deps, proposal_id: u64, voter: String) -> StdResult<VoteResponse> {
+    let voter = deps.api.addr_validate(&voter)?;
+    let proposal = load_proposal(deps.storage, proposal_id)?;
+    Ok(VoteResponse {
+        votes: proposal.votes_of(&voter),
+    })
+}
+
+pub fn query_votes_by_voter(
+    deps: Deps,
+    voter: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<VoterVoteListResponse> {
+    let voter = deps.api.addr_validate(&voter)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive_int);
+    let ids = VOTERS
+        .prefix_de(&voter)
+        .keys_de(deps.storage, start, None, Order::Ascending)
+        .take(limit);
+
+    let mut votes = vec![];
+    for proposal_id in ids {
+        let proposal_id = proposal_id?;
+        let proposal = load_proposal(deps.storage, proposal_id)?;
+        let locked = STAKED.has(deps.storage, (&voter, U64Key::new(proposal_id)));
+        votes.extend(
+            proposal
+                .votes_of(&voter)
+                .into_iter()
+                .map(|(position, amount)| VoterVoteResponse {
+                    proposal_id,
+                    position,
+                    amount,
+                    locked,
+                }),
+        );
+    }
+    Ok(VoterVoteListResponse { votes })
+}
+
+pub(crate) fn handle_withdrawal(
+    deps: DepsMut,
+    _env: Env,
     info: MessageInfo,
     msg: WithdrawVoteMsg,
 ) -> Result<Response, ContractError> {
-    let mut withdrawn = Uint128::zero();
-    PROPOSALS.update(deps.storage, |mut proposals| -> Result<_, ContractError> {
-        match proposals.get_mut(msg.proposal_id as usize) {
-            Some(proposal) => {
-                if proposal.status != ProposalStatus::Pending {
-                    return Err(ContractError::VoteOnCompletedProposal);
-                }
+    let mut proposal = load_proposal(deps.storage, msg.proposal_id)?;
+    if proposal.status != ProposalStatus::Pending {
+        return Err(ContractError::VoteOnCompletedProposal);
+    }
 
-                for (addr, amount) in proposal
-                    .yes
-                    .iter_mut()
-                    .chain(proposal.no.iter_mut().chain(proposal.abstain.iter_mut()))
-                {
-                    if *addr == info.sender {
-                        withdrawn += *amount;
-                        *amount = Uint128::zero();
-                    }
-                }
-                Ok(proposals)
-            }
-            None => Err(ContractError::Std(StdError::NotFound {
-                kind: format!("no such proposal ID ({})", msg.proposal_id),
-            })),
-        }
-    })?;
-    tokens::execute_transfer(
-        deps.branch(),
-        env.clone(),
-        MessageInfo {
-            sender: env.contract.address.clone(),
-            funds: vec![],
-        },
-        info.sender.to_string(),
-        withdrawn,
-    )
+    let withdrawn = proposal.remove_votes(&info.sender);
+    proposals().save(deps.storage, U64Key::new(msg.proposal_id), &proposal)?;
+    VOTERS.remove(deps.storage, (&info.sender, U64Key::new(msg.proposal_id)));
+    if withdrawn.is_zero() {
+        return Err(ContractError::InvalidZeroAmount);
+    }
+    tokens::unstake(deps.storage, &info.sender, msg.proposal_id, withdrawn)?;
+
+    Ok(Response::new()
+        .add_event(events::vote_withdrawn(
+            msg.proposal_id,
+            &info.sender,
+            withdrawn,
+        ))
+        .add_attribute("method", "withdraw")
+        .add_attribute("proposal_id", msg.proposal_id.to_string())
+        .add_attribute("tokens", withdrawn))
+}
+
+/// Withdraws the sender's votes from every proposal that is still
+/// being voted on, looking at no more than `limit` of the stakes they
+/// hold after `start_after`. Stakes are removed once claimed so
+/// votes on long finished proposals are not looked at again. The
+/// tokens are returned to the sender's balance in one update.
+pub(crate) fn handle_withdraw_all(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive_int);
+    let ids = STAKED
+        .prefix_de(&info.sender)
+        .keys_de(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<u64>>>()?;
+    let last = match ids.last() {
+        Some(last) => *last,
+        None => return Err(ContractError::InvalidZeroAmount),
+    };
+
+    let mut stakes = vec![];
+    let mut events = vec![];
+    for proposal_id in ids {
+        let mut proposal = load_proposal(deps.storage, proposal_id)?;
+        if proposal.status != ProposalStatus::Pending {
+            continue;
+        }
+        let delegated = proposal.delegated.len();
+        let withdrawn = proposal.remove_votes(&info.sender);
+        // Nothing changed if the sender only holds the deposit.
+        if withdrawn.is_zero() && proposal.delegated.len() == delegated {
+            continue;
+        }
+        proposals().save(deps.storage, U64Key::new(proposal_id), &proposal)?;
+        VOTERS.remove(deps.storage, (&info.sender, U64Key::new(proposal_id)));
+        if !withdrawn.is_zero() {
+            stakes.push((proposal_id, withdrawn));
+            events.push(events::vote_withdrawn(proposal_id, &info.sender, withdrawn));
+        }
+    }
+    let withdrawn = tokens::unstake_many(deps.storage, &info.sender, &stakes)?;
+
+    let ids = stakes
+        .iter()
+        .map(|(id, _)| id.to_string())
+        .collect::<Vec<_>>()
+        .join(",");
+    Ok(Response::new()
+        .add_events(events)
+        .add_attribute("method", "withdraw_all")
+        .add_attribute("proposal_ids", ids)
+        .add_attribute("last_proposal_id", last.to_string())
+        .add_attribute("tokens", withdrawn))
 }
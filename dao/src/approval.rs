@@ -0,0 +1,176 @@
+use cosmwasm_std::{Deps, DepsMut, Env, MessageInfo, Order, Response, StdResult, Storage, Uint128};
+use cw_storage_plus::U64Key;
+
+use crate::actions::{load_proposal, proposal_response, refund_deposit};
+use crate::events::{self, status_name};
+use crate::msg::{ApprovalQueueResponse, PreProposeInfo};
+use crate::pause;
+use crate::state::{proposals, PreProposeConfig, Proposal, ProposalStatus, STATE};
+use crate::tokens;
+use crate::vesting;
+use crate::ContractError;
+
+impl PreProposeInfo {
+    pub fn into_config(self, deps: Deps) -> Result<PreProposeConfig, ContractError> {
+        let threshold = self.sponsor_threshold.filter(|t| !t.is_zero());
+        if self.approvers.is_empty() && threshold.is_none() {
+            return Err(ContractError::InvalidPreProposeConfig);
+        }
+        let approvers = self
+            .approvers
+            .iter()
+            .map(|a| deps.api.addr_validate(a))
+            .collect::<StdResult<_>>()?;
+        Ok(PreProposeConfig {
+            approvers,
+            sponsor_threshold: threshold,
+        })
+    }
+}
+
+/// Loads a proposal that is waiting for approval.
+fn load_queued(storage: &dyn Storage, proposal_id: u64) -> Result<Proposal, ContractError> {
+    let proposal = load_proposal(storage, proposal_id)?;
+    if proposal.status != ProposalStatus::PendingApproval {
+        return Err(ContractError::NotAwaitingApproval);
+    }
+    Ok(proposal)
+}
+
+/// Takes a proposal out of the queue and returns its sponsorships.
+fn dequeue(
+    deps: DepsMut,
+    proposal_id: u64,
+    proposal: &mut Proposal,
+    status: ProposalStatus,
+) -> StdResult<()> {
+    for (sponsor, amount) in proposal.sponsors.iter() {
+        tokens::unstake(deps.storage, sponsor, proposal_id, *amount)?;
+    }
+    proposal.sponsors.clear();
+    proposal.status = status;
+    Ok(())
+}
+
+pub fn execute_approve(
+    mut deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    proposal_id: u64,
+) -> Result<Response, ContractError> {
+    let config = STATE.load(deps.storage)?.pre_propose;
+    if !config.is_some_and(|c| c.approvers.contains(&info.sender)) {
+        return Err(ContractError::Unauthorized);
+    }
+
+    let mut proposal = load_queued(deps.storage, proposal_id)?;
+    pause::assert_not_paused(deps.storage, Some(&proposal.action))?;
+    dequeue(
+        deps.branch(),
+        proposal_id,
+        &mut proposal,
+        ProposalStatus::Pending,
+    )?;
+    proposals().save(deps.storage, U64Key::new(proposal_id), &proposal)?;
+
+    Ok(Response::new()
+        .add_event(events::proposal_status_changed(
+            proposal_id,
+            &proposal.status,
+        ))
+        .add_attribute("method", "approve")
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_attribute("approver", info.sender))
+}
+
+pub fn execute_sponsor(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    proposal_id: u64,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let threshold = STATE
+        .load(deps.storage)?
+        .pre_propose
+        .and_then(|c| c.sponsor_threshold)
+        .ok_or(ContractError::Unauthorized)?;
+
+    let mut proposal = load_queued(deps.storage, proposal_id)?;
+    pause::assert_not_paused(deps.storage, Some(&proposal.action))?;
+
+    vesting::assert_unlocked(deps.storage, &env.block, &info.sender, amount)?;
+    tokens::stake(deps.storage, &info.sender, proposal_id, amount)?;
+    proposal.sponsors.push((info.sender.clone(), amount));
+
+    let sponsored: Uint128 = proposal.sponsors.iter().map(|(_, amount)| *amount).sum();
+    let approved = sponsored >= threshold;
+    if approved {
+        dequeue(
+            deps.branch(),
+            proposal_id,
+            &mut proposal,
+            ProposalStatus::Pending,
+        )?;
+    }
+    proposals().save(deps.storage, U64Key::new(proposal_id), &proposal)?;
+
+    let res = if approved {
+        Response::new().add_event(events::proposal_status_changed(
+            proposal_id,
+            &proposal.status,
+        ))
+    } else {
+        Response::new()
+    };
+    Ok(res
+        .add_attribute("method", "sponsor")
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_attribute("tokens", amount)
+        .add_attribute("approved", approved.to_string()))
+}
+
+/// Takes a proposal out of the queue and refunds its deposit. Allowed
+/// while the DAO is paused so that deposits are never stuck.
+pub fn execute_withdraw_proposal(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    proposal_id: u64,
+) -> Result<Response, ContractError> {
+    let mut proposal = load_queued(deps.storage, proposal_id)?;
+    if proposal.proposer != info.sender {
+        return Err(ContractError::Unauthorized);
+    }
+
+    dequeue(
+        deps.branch(),
+        proposal_id,
+        &mut proposal,
+        ProposalStatus::Withdrawn,
+    )?;
+    proposal.completed = Some((&env.block).into());
+    let messages = refund_deposit(deps.branch(), proposal_id, &proposal)?;
+    proposals().save(deps.storage, U64Key::new(proposal_id), &proposal)?;
+
+    Ok(Response::new()
+        .add_submessages(messages)
+        .add_event(events::proposal_completed(proposal_id, &proposal))
+        .add_attribute("method", "withdraw_proposal")
+        .add_attribute("proposal_id", proposal_id.to_string()))
+}
+
+pub fn query_approval_queue(deps: Deps, env: Env) -> StdResult<ApprovalQueueResponse> {
+    let proposals = proposals()
+        .idx
+        .status
+        .prefix(
+            status_name(&ProposalStatus::PendingApproval)
+                .as_bytes()
+                .to_vec(),
+        )
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, p)| proposal_response(p, &env.block)))
+        .collect::<StdResult<_>>()?;
+    Ok(ApprovalQueueResponse { proposals })
+}
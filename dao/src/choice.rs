@@ -0,0 +1,171 @@
+use cosmwasm_std::{Deps, StdError, StdResult, Uint128};
+
+use crate::actions::load_proposal;
+use crate::msg::{ChoiceTallyResponse, ProposeAction, VotePosition};
+use crate::quadratic;
+use crate::state::Proposal;
+use crate::ContractError;
+
+fn invalid(reason: &str) -> ContractError {
+    ContractError::InvalidChoice {
+        reason: reason.to_string(),
+    }
+}
+
+/// The number of choices on a proposal or None if it is not a
+/// multiple choice proposal.
+pub fn choice_count(proposal: &Proposal) -> Option<usize> {
+    match &proposal.action {
+        ProposeAction::MultipleChoice { choices } => Some(choices.len()),
+        _ => None,
+    }
+}
+
+/// Checks that a vote's ranking makes sense for the proposal. Yes
+/// votes on multiple choice proposals must rank at least one choice
+/// and no vote may rank a choice twice. Other votes must not rank
+/// anything.
+pub(crate) fn validate_ranking(
+    proposal: &Proposal,
+    position: &VotePosition,
+    ranking: &[u32],
+) -> Result<(), ContractError> {
+    let count = match choice_count(proposal) {
+        Some(count) => count,
+        None if ranking.is_empty() => return Ok(()),
+        None => return Err(invalid("only multiple choice proposals take a ranking")),
+    };
+    match position {
+        VotePosition::Yes => {
+            if ranking.is_empty() {
+                return Err(invalid("yes votes must rank at least one choice"));
+            }
+            for (i, choice) in ranking.iter().enumerate() {
+                if *choice as usize >= count {
+                    return Err(invalid("no such choice"));
+                }
+                if ranking[..i].contains(choice) {
+                    return Err(invalid("choices may only be ranked once"));
+                }
+            }
+            Ok(())
+        }
+        VotePosition::Abstain if ranking.is_empty() => Ok(()),
+        VotePosition::Abstain => Err(invalid("abstaining votes can not rank choices")),
+        _ => Err(invalid(
+            "multiple choice proposals only take yes or abstain votes",
+        )),
+    }
+}
+
+/// The weight behind each ranking on the proposal. Power used by a
+/// delegate follows the delegate's own ranking. Ballots on quadratic
+/// proposals carry their share of the voter's quadratic weight.
+fn ballots(proposal: &Proposal) -> StdResult<Vec<(Uint128, &[u32])>> {
+    let direct = proposal
+        .ballots
+        .iter()
+        .filter(|b| !b.amount.is_zero())
+        .map(|b| (&b.voter, b.amount, b.ranking.as_slice()));
+    let delegated = proposal
+        .delegated
+        .iter()
+        .filter(|d| d.position == VotePosition::Yes)
+        .filter_map(|d| {
+            proposal
+                .ballots
+                .iter()
+                .find(|b| b.voter == d.delegate)
+                .map(|b| (&d.delegator, d.power, b.ranking.as_slice()))
+        });
+    let ballots = direct.chain(delegated);
+    if !proposal.quadratic {
+        return Ok(ballots
+            .map(|(_, amount, ranking)| (amount, ranking))
+            .collect());
+    }
+    let totals = quadratic::voter_stakes(proposal)?;
+    Ok(ballots
+        .map(|(voter, amount, ranking)| {
+            let total = totals.get(voter).copied().unwrap_or_default();
+            (quadratic::share(amount, total), ranking)
+        })
+        .collect())
+}
+
+/// Counts each ballot towards its highest ranked choice that has not
+/// been eliminated.
+fn count(ballots: &[(Uint128, &[u32])], eliminated: &[bool]) -> StdResult<Vec<Uint128>> {
+    let mut tallies = vec![Uint128::zero(); eliminated.len()];
+    for (amount, ranking) in ballots {
+        if let Some(choice) = ranking.iter().find(|c| !eliminated[**c as usize]) {
+            let tally = &mut tallies[*choice as usize];
+            *tally = tally.checked_add(*amount)?;
+        }
+    }
+    Ok(tallies)
+}
+
+/// The weight of the votes ranking each choice first.
+pub fn first_choices(proposal: &Proposal) -> StdResult<Vec<Uint128>> {
+    let choices = choice_count(proposal).unwrap_or_default();
+    count(&ballots(proposal)?, &vec![false; choices])
+}
+
+/// Decides a multiple choice proposal by instant runoff. Each round
+/// the choice with the least support is eliminated and its ballots
+/// move to their next preference, until one choice has a majority
+/// of the ballots that still rank a remaining choice. Ties for last
+/// place eliminate the later choice. Returns None if nobody ranked
+/// any choice.
+pub fn instant_runoff(proposal: &Proposal) -> StdResult<Option<u32>> {
+    let choices = choice_count(proposal).unwrap_or_default();
+    let ballots = ballots(proposal)?;
+    let mut eliminated = vec![false; choices];
+    loop {
+        let tallies = count(&ballots, &eliminated)?;
+        let remaining: Vec<usize> = (0..choices)
+            .filter(|c| !eliminated[*c] && !tallies[*c].is_zero())
+            .collect();
+        let active = remaining
+            .iter()
+            .try_fold(Uint128::zero(), |sum, c| sum.checked_add(tallies[*c]))?;
+
+        let leader = remaining
+            .iter()
+            .max_by_key(|c| (tallies[**c], choices - **c));
+        let last = remaining
+            .iter()
+            .min_by_key(|c| (tallies[**c], choices - **c));
+        let (leader, last) = match (leader, last) {
+            (Some(leader), Some(last)) => (*leader, *last),
+            _ => return Ok(None),
+        };
+        if remaining.len() == 1 || tallies[leader] > active - tallies[leader] {
+            return Ok(Some(leader as u32));
+        }
+
+        // Choices with no support are dropped along with the weakest.
+        for c in 0..choices {
+            if tallies[c].is_zero() {
+                eliminated[c] = true;
+            }
+        }
+        eliminated[last] = true;
+    }
+}
+
+pub fn query_choice_tally(deps: Deps, proposal_id: u64) -> StdResult<ChoiceTallyResponse> {
+    let proposal = &load_proposal(deps.storage, proposal_id)?;
+    if choice_count(proposal).is_none() {
+        return Err(StdError::generic_err("not a multiple choice proposal"));
+    }
+    let winner = match proposal.winner {
+        Some(winner) => Some(winner),
+        None => instant_runoff(proposal)?,
+    };
+    Ok(ChoiceTallyResponse {
+        first_choices: first_choices(proposal)?,
+        winner,
+    })
+}
@@ -0,0 +1,114 @@
+use cosmwasm_std::{Coin, Decimal, Deps, Event, StdResult, Uint128};
+use cw0::Duration;
+use cw2::get_contract_version;
+
+use crate::events;
+use crate::msg::{ConfigResponse, ConfigUpdate};
+use crate::state::{State, STATE, TOKEN_INFO};
+use crate::ContractError;
+
+impl ConfigUpdate {
+    /// Checks the update as a whole. `total_supply` is the current
+    /// supply of voting tokens.
+    pub fn validate(&self, total_supply: Uint128) -> Result<(), ContractError> {
+        if *self == ConfigUpdate::default() {
+            return Err(ContractError::EmptyConfigUpdate);
+        }
+        if (self.native_proposal_cost.is_some() && self.clear_native_proposal_cost)
+            || (self.veto_threshold.is_some() && self.clear_veto_threshold)
+            || (self.timelock.is_some() && self.clear_timelock)
+        {
+            return Err(ContractError::ConflictingConfigUpdate);
+        }
+        if matches!(self.quorum, Some(q) if q.is_zero() || q > total_supply) {
+            return Err(ContractError::InvalidQuorum);
+        }
+        if matches!(self.veto_threshold, Some(t) if t > Decimal::one()) {
+            return Err(ContractError::InvalidVetoThreshold);
+        }
+        if matches!(&self.cancel_policy, Some(p) if p.fee > Decimal::one()) {
+            return Err(ContractError::InvalidCancelFee);
+        }
+        Ok(())
+    }
+
+    /// Applies the update to `state` and returns a `config_changed`
+    /// event for each setting that was set.
+    pub(crate) fn apply(&self, state: &mut State) -> StdResult<Vec<Event>> {
+        let mut changed = vec![];
+        if let Some(quorum) = self.quorum {
+            state.quorum = quorum;
+            changed.push(events::config_changed("quorum", &quorum)?);
+        }
+        if let Some(cost) = self.proposal_cost {
+            state.proposal_cost = cost;
+            changed.push(events::config_changed("proposal_cost", &cost)?);
+        }
+        if let Some(cost) = &self.native_proposal_cost {
+            state.native_proposal_cost = Some(cost.clone());
+            changed.push(events::config_changed("native_proposal_cost", cost)?);
+        }
+        if self.clear_native_proposal_cost {
+            state.native_proposal_cost = None;
+            changed.push(events::config_changed(
+                "native_proposal_cost",
+                &None::<Coin>,
+            )?);
+        }
+        if let Some(can_vote) = self.locked_tokens_can_vote {
+            state.locked_tokens_can_vote = can_vote;
+            changed.push(events::config_changed("locked_tokens_can_vote", &can_vote)?);
+        }
+        if let Some(policy) = &self.deposit_policy {
+            state.deposit_policy = policy.clone();
+            changed.push(events::config_changed("deposit_policy", policy)?);
+        }
+        if let Some(threshold) = self.veto_threshold {
+            state.veto_threshold = Some(threshold);
+            changed.push(events::config_changed("veto_threshold", &threshold)?);
+        }
+        if self.clear_veto_threshold {
+            state.veto_threshold = None;
+            changed.push(events::config_changed("veto_threshold", &None::<Decimal>)?);
+        }
+        if let Some(quadratic) = self.quadratic {
+            state.quadratic = quadratic;
+            changed.push(events::config_changed("quadratic", &quadratic)?);
+        }
+        if let Some(policy) = &self.cancel_policy {
+            state.cancel_policy = policy.clone();
+            changed.push(events::config_changed("cancel_policy", policy)?);
+        }
+        if let Some(timelock) = self.timelock {
+            state.timelock = Some(timelock);
+            changed.push(events::config_changed("timelock", &timelock)?);
+        }
+        if self.clear_timelock {
+            state.timelock = None;
+            changed.push(events::config_changed("timelock", &None::<Duration>)?);
+        }
+        Ok(changed)
+    }
+}
+
+pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
+    let state = STATE.load(deps.storage)?;
+    Ok(ConfigResponse {
+        quorum: state.quorum,
+        proposal_cost: state.proposal_cost,
+        native_proposal_cost: state.native_proposal_cost,
+        locked_tokens_can_vote: state.locked_tokens_can_vote,
+        deposit_policy: state.deposit_policy,
+        veto_threshold: state.veto_threshold,
+        quadratic: state.quadratic,
+        conviction: state.conviction,
+        pre_propose: state.pre_propose,
+        cancel_policy: state.cancel_policy,
+        timelock: state.timelock,
+        guardian: state.guardian,
+        paused: state.paused,
+        transfers_paused: state.transfers_paused,
+        token_mode: TOKEN_INFO.load(deps.storage)?.mode,
+        version: get_contract_version(deps.storage)?,
+    })
+}
@@ -1,15 +1,28 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdError, StdResult,
+    to_binary, Binary, Decimal, Deps, DepsMut, Env, MessageInfo, Reply, Response, StdResult,
 };
+use cw0::nonpayable;
 use cw2::set_contract_version;
 
 use crate::actions;
+use crate::approval;
+use crate::choice;
+use crate::config;
+use crate::conviction;
+use crate::delegation;
 use crate::error::ContractError;
-use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
-use crate::state::{State, TokenInfo, ITEMS, PROPOSALS, STATE, TOKEN_INFO};
+use crate::msg::{
+    ExecuteMsg, InstantiateMsg, ProposalCostResponse, QueryMsg, QuorumResponse, TokenModeResponse,
+};
+use crate::pause;
+use crate::ragequit;
+use crate::refunds;
+use crate::state::{State, TokenInfo, ITEMS, PROPOSAL_COUNT, STATE, TOKEN_INFO};
 use crate::tokens::{self, create_accounts};
+use crate::treasury;
+use crate::vesting;
 
 // version info for migration info
 const CONTRACT_NAME: &str = "webdao";
@@ -27,13 +40,34 @@ pub fn instantiate(
     // Validate the token info and then set up initial balances. We
     // infer total supply from the initial balances.
     msg.token_info.validate()?;
-    let total_supply = create_accounts(&mut deps, &msg.token_info.initial_balances)?;
+    let total_supply = create_accounts(
+        &mut deps,
+        &msg.token_info.initial_balances,
+        &msg.token_info.vesting,
+    )?;
 
     // Assert that the quorum is not zero and that it is less than the
     // total token supply.
     if msg.quorum.is_zero() || msg.quorum > total_supply {
         return Err(ContractError::InvalidQuorum);
     }
+    if matches!(msg.veto_threshold, Some(t) if t > Decimal::one()) {
+        return Err(ContractError::InvalidVetoThreshold);
+    }
+    if let Some(config) = &msg.conviction {
+        config.validate()?;
+    }
+    if msg.cancel_policy.fee > Decimal::one() {
+        return Err(ContractError::InvalidCancelFee);
+    }
+    let guardian = msg
+        .guardian
+        .map(|g| deps.api.addr_validate(&g))
+        .transpose()?;
+    let pre_propose = msg
+        .pre_propose
+        .map(|info| info.into_config(deps.as_ref()))
+        .transpose()?;
 
     // Store information about the token for later queries.
     let token_info = TokenInfo {
@@ -41,6 +75,7 @@ pub fn instantiate(
         symbol: msg.token_info.symbol,
         decimals: msg.token_info.decimals,
         total_supply,
+        mode: msg.token_info.mode,
     };
     TOKEN_INFO.save(deps.storage, &token_info)?;
 
@@ -48,14 +83,26 @@ pub fn instantiate(
     let state = State {
         quorum: msg.quorum,
         proposal_cost: msg.proposal_cost,
+        native_proposal_cost: msg.native_proposal_cost,
+        locked_tokens_can_vote: msg.locked_tokens_can_vote,
+        deposit_policy: msg.deposit_policy,
+        veto_threshold: msg.veto_threshold,
+        quadratic: msg.quadratic,
+        conviction: msg.conviction,
+        pre_propose,
+        cancel_policy: msg.cancel_policy,
+        timelock: msg.timelock,
+        guardian,
+        paused: false,
+        transfers_paused: false,
     };
     STATE.save(deps.storage, &state)?;
 
     // Set up proposal state.
-    let proposals = vec![];
-    PROPOSALS.save(deps.storage, &proposals)?;
+    PROPOSAL_COUNT.save(deps.storage, &0)?;
     let items = vec![];
     ITEMS.save(deps.storage, &items)?;
+    treasury::update_cw20_tokens(deps.branch(), &msg.cw20_tokens, &[])?;
 
     Ok(Response::new()
         .add_attribute("method", "instantiate")
@@ -71,8 +118,16 @@ pub fn execute(
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
+    // Proposals are the only messages that native coins may be sent
+    // along with.
+    if !matches!(msg, ExecuteMsg::Propose(_)) {
+        nonpayable(&info)?;
+    }
     match msg {
         ExecuteMsg::Withdraw(w) => actions::handle_withdrawal(deps, env, info, w),
+        ExecuteMsg::WithdrawAll { start_after, limit } => {
+            actions::handle_withdraw_all(deps, env, info, start_after, limit)
+        }
         ExecuteMsg::Transfer { recipient, amount } => {
             tokens::execute_transfer(deps, env, info, recipient, amount)
         }
@@ -84,53 +139,188 @@ pub fn execute(
         } => tokens::execute_send(deps, env, info, contract, amount, msg),
         ExecuteMsg::Propose(p) => actions::handle_propose(deps, env, info, p),
         ExecuteMsg::Vote(v) => actions::handle_vote(deps, env, info, v),
+        ExecuteMsg::VoteWeighted {
+            proposal_id,
+            amount,
+            options,
+        } => actions::handle_vote_weighted(deps, env, info, proposal_id, amount, options),
+        ExecuteMsg::Cancel { proposal_id } => actions::handle_cancel(deps, env, info, proposal_id),
+        ExecuteMsg::ExecuteProposal { proposal_id } => {
+            actions::handle_execute_proposal(deps, env, info, proposal_id)
+        }
+        ExecuteMsg::Pause { transfers } => pause::execute_pause(deps, info, transfers),
+        ExecuteMsg::RageQuit { amount } => ragequit::execute_rage_quit(deps, env, info, amount),
+        ExecuteMsg::Approve { proposal_id } => {
+            approval::execute_approve(deps, env, info, proposal_id)
+        }
+        ExecuteMsg::Sponsor {
+            proposal_id,
+            amount,
+        } => approval::execute_sponsor(deps, env, info, proposal_id, amount),
+        ExecuteMsg::WithdrawProposal { proposal_id } => {
+            approval::execute_withdraw_proposal(deps, env, info, proposal_id)
+        }
+        ExecuteMsg::ProposeGrant(p) => conviction::execute_propose_grant(deps, env, info, p),
+        ExecuteMsg::StakeConviction {
+            proposal_id,
+            amount,
+        } => conviction::execute_stake(deps, env, info, proposal_id, amount),
+        ExecuteMsg::UnstakeConviction {
+            proposal_id,
+            amount,
+        } => conviction::execute_unstake(deps, env, info, proposal_id, amount),
+        ExecuteMsg::UpdateConviction { proposal_id } => {
+            conviction::execute_update(deps, env, proposal_id)
+        }
+        ExecuteMsg::Delegate { delegate } => {
+            delegation::execute_delegate(deps, env, info, delegate)
+        }
+        ExecuteMsg::Undelegate => delegation::execute_undelegate(deps, env, info),
+        ExecuteMsg::ClaimRefund { proposal_id } => {
+            refunds::execute_claim_refund(deps, env, info, proposal_id)
+        }
     }
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    match msg.id {
+        ragequit::RAGE_QUIT_REPLY_ID => ragequit::reply_rage_quit(deps),
+        id => Err(ContractError::UnknownReplyId { id }),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
-        QueryMsg::ListProposals => Ok(to_binary(&PROPOSALS.load(deps.storage)?)?),
+        QueryMsg::ListProposals => to_binary(&actions::query_proposals(deps, env)?),
         QueryMsg::GetProposal { proposal_id } => {
-            let proposals = PROPOSALS.load(deps.storage)?;
-            Ok(to_binary(proposals.get(proposal_id as usize).ok_or(
-                StdError::NotFound {
-                    kind: format!("no such proposal ID ({})", proposal_id),
-                },
-            )?)?)
+            to_binary(&actions::query_proposal(deps, env, proposal_id)?)
         }
-        QueryMsg::ListItems => Ok(to_binary(&ITEMS.load(deps.storage)?)?),
-        QueryMsg::GetItem { item_id } => {
-            let items = ITEMS.load(deps.storage)?;
-            Ok(to_binary(items.get(item_id as usize).ok_or(
-                StdError::NotFound {
-                    kind: format!("no such item ID ({})", item_id),
-                },
-            )?)?)
+        QueryMsg::ListProposalsByStatus {
+            status,
+            start_after,
+            limit,
+        } => to_binary(&actions::query_proposals_by_status(
+            deps,
+            env,
+            status,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::ListProposalsByProposer {
+            proposer,
+            start_after,
+            limit,
+        } => to_binary(&actions::query_proposals_by_proposer(
+            deps,
+            env,
+            proposer,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::ReverseProposals {
+            start_before,
+            limit,
+        } => to_binary(&actions::query_reverse_proposals(
+            deps,
+            env,
+            start_before,
+            limit,
+        )?),
+        QueryMsg::ChoiceTally { proposal_id } => {
+            to_binary(&choice::query_choice_tally(deps, proposal_id)?)
+        }
+        QueryMsg::GetVote { proposal_id, voter } => {
+            to_binary(&actions::query_vote(deps, proposal_id, voter)?)
+        }
+        QueryMsg::ListVotesByVoter {
+            voter,
+            start_after,
+            limit,
+        } => to_binary(&actions::query_votes_by_voter(
+            deps,
+            voter,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::ClaimableRefunds {
+            address,
+            start_after,
+            limit,
+        } => to_binary(&refunds::query_claimable_refunds(
+            deps,
+            address,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::ListGrants => to_binary(&conviction::query_grants(deps)?),
+        QueryMsg::GetGrant { proposal_id } => {
+            to_binary(&conviction::query_grant(deps, proposal_id)?)
         }
+        QueryMsg::Conviction { proposal_id } => {
+            to_binary(&conviction::query_conviction(deps, env, proposal_id)?)
+        }
+        QueryMsg::ApprovalQueue => to_binary(&approval::query_approval_queue(deps, env)?),
+        QueryMsg::ListItems => to_binary(&actions::query_items(deps)?),
+        QueryMsg::GetItem { item_id } => to_binary(&actions::query_item(deps, item_id)?),
+        QueryMsg::Config => to_binary(&config::query_config(deps)?),
         QueryMsg::GetQuorum => {
             let state = STATE.load(deps.storage)?;
-            Ok(to_binary(&state.quorum)?)
+            to_binary(&QuorumResponse {
+                quorum: state.quorum,
+            })
         }
         QueryMsg::GetProposalCost => {
             let state = STATE.load(deps.storage)?;
-            Ok(to_binary(&state.proposal_cost)?)
+            to_binary(&ProposalCostResponse {
+                proposal_cost: state.proposal_cost,
+                native_proposal_cost: state.native_proposal_cost,
+            })
         }
         QueryMsg::Balance { address } => to_binary(&tokens::query_balance(deps, address)?),
-        QueryMsg::TokenInfo {} => to_binary(&tokens::query_token_info(deps)?),
+        QueryMsg::StakedBalance { address } => {
+            to_binary(&tokens::query_staked_balance(deps, address)?)
+        }
+        QueryMsg::TreasuryBalance => to_binary(&tokens::query_treasury_balance(deps, env)?),
+        QueryMsg::TreasuryBalances => to_binary(&treasury::query_treasury_balances(deps, env)?),
+        QueryMsg::Cw20Tokens => to_binary(&treasury::query_cw20_tokens(deps)?),
+        QueryMsg::TokenInfo => to_binary(&tokens::query_token_info(deps)?),
+        QueryMsg::TokenMode => to_binary(&TokenModeResponse {
+            mode: TOKEN_INFO.load(deps.storage)?.mode,
+        }),
+        QueryMsg::VestingInfo { address } => {
+            to_binary(&vesting::query_vesting_info(deps, &env.block, address)?)
+        }
+        QueryMsg::Delegate { address } => to_binary(&delegation::query_delegate(deps, address)?),
+        QueryMsg::PauseInfo => to_binary(&pause::query_pause_info(deps)?),
+        QueryMsg::VotingPower { address } => {
+            to_binary(&delegation::query_voting_power(deps, env, address)?)
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::msg::{
-        DaoItem, ProposeAction, ProposeMsg, TokenInstantiateInfo, VoteMsg, WithdrawVoteMsg,
+        ApprovalQueueResponse, CancelPolicy, Choice, ChoiceTallyResponse,
+        ClaimableRefundListResponse, ClaimableRefundResponse, ConfigResponse, ConfigUpdate,
+        ConvictionConfig, ConvictionResponse, DaoItem, DelegateResponse, DepositForfeit,
+        DepositPolicy, DepositRefund, GrantProposeMsg, ItemListResponse, ItemResponse,
+        PauseInfoResponse, PreProposeInfo, ProposalListResponse, ProposalResponse, ProposeAction,
+        ProposeMsg, TokenInstantiateInfo, TokenMode, TreasuryBalancesResponse, VestingGrant,
+        VestingInfoResponse, VestingSchedule, VestingUnit, VoteMsg, VoteResponse,
+        VoterVoteListResponse, VoterVoteResponse, VotingPowerResponse, WithdrawVoteMsg,
     };
-    use crate::state::{Proposal, ProposalStatus};
+    use crate::state::{proposals, BlockStamp, Proposal, ProposalStatus};
 
     use super::*;
     use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info, MOCK_CONTRACT_ADDR};
-    use cosmwasm_std::{from_binary, CosmosMsg, SubMsg, Uint128, WasmMsg};
+    use cosmwasm_std::{
+        coin, coins, from_binary, Addr, BankMsg, CosmosMsg, Event, Order, StdError, Storage,
+        SubMsg, Uint128, WasmMsg,
+    };
+    use cw0::{Duration, PaymentError};
     use cw20::{BalanceResponse, Cw20Coin, Cw20ReceiveMsg, TokenInfoResponse};
 
     #[test]
@@ -148,7 +338,9 @@ mod tests {
                     address: "awallet".to_string(),
                     amount: Uint128::from(100000u128),
                 }],
+                ..Default::default()
             },
+            ..Default::default()
         };
         let info = mock_info("creator", &[]);
 
@@ -156,11 +348,13 @@ mod tests {
         assert_eq!(0, res.messages.len());
 
         let res = query(deps.as_ref(), mock_env(), QueryMsg::GetQuorum {}).unwrap();
-        let value: Uint128 = from_binary(&res).unwrap();
+        let value = from_binary::<QuorumResponse>(&res).unwrap().quorum;
         assert_eq!(Uint128::from(30u128), value);
 
         let res = query(deps.as_ref(), mock_env(), QueryMsg::GetProposalCost {}).unwrap();
-        let value: Uint128 = from_binary(&res).unwrap();
+        let value = from_binary::<ProposalCostResponse>(&res)
+            .unwrap()
+            .proposal_cost;
         assert_eq!(Uint128::from(1u128), value);
 
         let res = query(deps.as_ref(), mock_env(), QueryMsg::TokenInfo {}).unwrap();
@@ -187,8 +381,8 @@ mod tests {
         assert_eq!(Uint128::from(100000u128), value.balance);
 
         let res = query(deps.as_ref(), mock_env(), QueryMsg::ListProposals).unwrap();
-        let value: Vec<Proposal> = from_binary(&res).unwrap();
-        assert_eq!(Vec::<Proposal>::new(), value);
+        let value: ProposalListResponse = from_binary(&res).unwrap();
+        assert_eq!(value.proposals, vec![]);
     }
 
     #[test]
@@ -209,7 +403,9 @@ mod tests {
                     address: "awallet".to_string(),
                     amount: Uint128::from(100000u128),
                 }],
+                ..Default::default()
             },
+            ..Default::default()
         };
         let info = mock_info("creator", &[]);
 
@@ -231,7 +427,9 @@ mod tests {
                     address: "🦄".to_string(),
                     amount: Uint128::from(100000u128),
                 }],
+                ..Default::default()
             },
+            ..Default::default()
         };
         let info = mock_info("🦄", &[]);
 
@@ -260,11 +458,12 @@ mod tests {
         )
         .unwrap();
 
-        // This working despire the response to the query being a
-        // state::Proposal type and not a ProposalMsg is an
-        // interesting quirk.. Good to keep in mind.
-        let value: ProposeMsg = from_binary(&res).unwrap();
-        assert_eq!(proposal, value);
+        let value: ProposalResponse = from_binary(&res).unwrap();
+        assert_eq!(value.id, 0);
+        assert_eq!(value.title, proposal.title);
+        assert_eq!(value.body, proposal.body);
+        assert_eq!(value.action, proposal.action);
+        assert_eq!(value.status, ProposalStatus::Pending);
 
         let res = query(
             deps.as_ref(),
@@ -296,7 +495,9 @@ mod tests {
                     address: "awallet".to_string(),
                     amount: Uint128::from(100000u128),
                 }],
+                ..Default::default()
             },
+            ..Default::default()
         };
         let info = mock_info("🦄", &[]);
 
@@ -332,7 +533,9 @@ mod tests {
                     address: "awallet".to_string(),
                     amount: Uint128::from(100000u128),
                 }],
+                ..Default::default()
             },
+            ..Default::default()
         };
         let info = mock_info("🦄", &[]);
 
@@ -377,7 +580,9 @@ mod tests {
                     address: "🦄".to_string(),
                     amount: Uint128::from(100000u128),
                 }],
+                ..Default::default()
             },
+            ..Default::default()
         };
         instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
 
@@ -402,6 +607,7 @@ mod tests {
             proposal_id: 0,
             position: crate::msg::VotePosition::Yes,
             amount: Uint128::from(97u128),
+            ranking: vec![],
         };
         execute(
             deps.as_mut(),
@@ -418,12 +624,9 @@ mod tests {
             QueryMsg::GetProposal { proposal_id: 0 },
         )
         .unwrap();
-        let prop: Proposal = from_binary(&res).unwrap();
+        let prop: ProposalResponse = from_binary(&res).unwrap();
         assert_eq!(prop.status, ProposalStatus::Pending);
-        assert_eq!(
-            prop.get_votes(crate::msg::VotePosition::Yes),
-            Uint128::from(97u128)
-        );
+        assert_eq!(prop.tally.yes, Uint128::from(97u128));
 
         // Also assert that funds have been deducted from the proposer
         // voter's accounts. 1 token for the proposal and 97 for the
@@ -439,17 +642,22 @@ mod tests {
         let value: BalanceResponse = from_binary(&res).unwrap();
         assert_eq!(value.balance, Uint128::from(99902u128));
 
-        // Make sure that the tokens have been correctly sent to the contract.
+        // Make sure that the tokens have been correctly staked and
+        // kept out of the treasury.
         let res = query(
             deps.as_ref(),
             mock_env(),
-            QueryMsg::Balance {
-                address: MOCK_CONTRACT_ADDR.to_string(),
+            QueryMsg::StakedBalance {
+                address: "🦄".to_string(),
             },
         )
         .unwrap();
         let value: BalanceResponse = from_binary(&res).unwrap();
-        assert_eq!(value.balance, Uint128::from(98u128))
+        assert_eq!(value.balance, Uint128::from(98u128));
+        assert_eq!(
+            get_balance(deps.as_ref(), MOCK_CONTRACT_ADDR),
+            Uint128::zero()
+        );
     }
 
     #[test]
@@ -464,6 +672,7 @@ mod tests {
             proposal_id: 0,
             position: crate::msg::VotePosition::Yes,
             amount: Uint128::from(1u128),
+            ranking: vec![],
         };
         execute(
             deps.as_mut(),
@@ -480,14 +689,13 @@ mod tests {
             QueryMsg::GetProposal { proposal_id: 0 },
         )
         .unwrap();
-        let prop: Proposal = from_binary(&res).unwrap();
+        let prop: ProposalResponse = from_binary(&res).unwrap();
         assert_eq!(prop.status, ProposalStatus::Passed);
-        assert_eq!(
-            prop.get_votes(crate::msg::VotePosition::Yes),
-            Uint128::from(98u128)
-        );
+        assert_eq!(prop.tally.yes, Uint128::from(98u128));
 
-        // Check that tokens have been returned correctly.
+        // Claim the stakes and check that tokens have been returned
+        // correctly.
+        claim_refund(deps.as_mut(), "🦄", 0);
         let res = query(
             deps.as_ref(),
             mock_env(),
@@ -512,6 +720,7 @@ mod tests {
             proposal_id: 0,
             position: crate::msg::VotePosition::No,
             amount: Uint128::from(1u128),
+            ranking: vec![],
         };
         execute(
             deps.as_mut(),
@@ -527,14 +736,13 @@ mod tests {
             QueryMsg::GetProposal { proposal_id: 0 },
         )
         .unwrap();
-        let prop: Proposal = from_binary(&res).unwrap();
+        let prop: ProposalResponse = from_binary(&res).unwrap();
         assert_eq!(prop.status, ProposalStatus::Passed);
-        assert_eq!(
-            prop.get_votes(crate::msg::VotePosition::Yes),
-            Uint128::from(97u128)
-        );
+        assert_eq!(prop.tally.yes, Uint128::from(97u128));
 
-        // Check that tokens have been returned correctly.
+        // Claim the stakes and check that tokens have been returned
+        // correctly.
+        claim_refund(deps.as_mut(), "🦄", 0);
         let res = query(
             deps.as_ref(),
             mock_env(),
@@ -558,6 +766,7 @@ mod tests {
             proposal_id: 0,
             position: crate::msg::VotePosition::No,
             amount: Uint128::from(97u128),
+            ranking: vec![],
         };
         execute(
             deps.as_mut(),
@@ -573,18 +782,14 @@ mod tests {
             QueryMsg::GetProposal { proposal_id: 0 },
         )
         .unwrap();
-        let prop: Proposal = from_binary(&res).unwrap();
+        let prop: ProposalResponse = from_binary(&res).unwrap();
         assert_eq!(prop.status, ProposalStatus::Failed);
-        assert_eq!(
-            prop.get_votes(crate::msg::VotePosition::Yes),
-            Uint128::from(97u128)
-        );
-        assert_eq!(
-            prop.get_votes(crate::msg::VotePosition::No),
-            Uint128::from(97u128)
-        );
+        assert_eq!(prop.tally.yes, Uint128::from(97u128));
+        assert_eq!(prop.tally.no, Uint128::from(97u128));
 
-        // Check that tokens have been returned correctly.
+        // Claim the stakes and check that tokens have been returned
+        // correctly.
+        claim_refund(deps.as_mut(), "🦄", 0);
         let res = query(
             deps.as_ref(),
             mock_env(),
@@ -596,9 +801,9 @@ mod tests {
         let balance: BalanceResponse = from_binary(&res).unwrap();
         assert_eq!(Uint128::from(100000u128), balance.balance);
 
-	// Check that the item was not added
-	let items = query(deps.as_ref(), mock_env(), QueryMsg::ListItems).unwrap();
-        let items: Vec<DaoItem> = from_binary(&items).unwrap();
+        // Check that the item was not added
+        let items = query(deps.as_ref(), mock_env(), QueryMsg::ListItems).unwrap();
+        let items = from_binary::<ItemListResponse>(&items).unwrap().items;
         assert_eq!(items.len(), 0);
     }
 
@@ -614,6 +819,7 @@ mod tests {
             proposal_id: 0,
             position: crate::msg::VotePosition::Abstain,
             amount: Uint128::from(1u128),
+            ranking: vec![],
         };
         execute(
             deps.as_mut(),
@@ -630,19 +836,15 @@ mod tests {
             QueryMsg::GetProposal { proposal_id: 0 },
         )
         .unwrap();
-        let prop: Proposal = from_binary(&res).unwrap();
+        let prop: ProposalResponse = from_binary(&res).unwrap();
 
         assert_eq!(prop.status, ProposalStatus::Passed);
-        assert_eq!(
-            prop.get_votes(crate::msg::VotePosition::Yes),
-            Uint128::from(97u128)
-        );
-        assert_eq!(
-            prop.get_votes(crate::msg::VotePosition::Abstain),
-            Uint128::from(1u128)
-        );
+        assert_eq!(prop.tally.yes, Uint128::from(97u128));
+        assert_eq!(prop.tally.abstain, Uint128::from(1u128));
 
-        // Check that tokens have been returned correctly.
+        // Claim the stakes and check that tokens have been returned
+        // correctly.
+        claim_refund(deps.as_mut(), "🦄", 0);
         let res = query(
             deps.as_ref(),
             mock_env(),
@@ -691,12 +893,9 @@ mod tests {
             QueryMsg::GetProposal { proposal_id: 0 },
         )
         .unwrap();
-        let prop: Proposal = from_binary(&res).unwrap();
+        let prop: ProposalResponse = from_binary(&res).unwrap();
         assert_eq!(prop.status, ProposalStatus::Pending);
-        assert_eq!(
-            prop.get_votes(crate::msg::VotePosition::Yes),
-            Uint128::zero()
-        );
+        assert_eq!(prop.tally.yes, Uint128::zero());
     }
 
     #[test]
@@ -714,6 +913,7 @@ mod tests {
             proposal_id: 0,
             position: crate::msg::VotePosition::No,
             amount: Uint128::from(1u128),
+            ranking: vec![],
         };
         execute(
             deps.as_mut(),
@@ -729,14 +929,13 @@ mod tests {
             QueryMsg::GetProposal { proposal_id: 0 },
         )
         .unwrap();
-        let prop: Proposal = from_binary(&res).unwrap();
+        let prop: ProposalResponse = from_binary(&res).unwrap();
         assert_eq!(prop.status, ProposalStatus::Passed);
-        assert_eq!(
-            prop.get_votes(crate::msg::VotePosition::Yes),
-            Uint128::from(97u128)
-        );
+        assert_eq!(prop.tally.yes, Uint128::from(97u128));
 
-        // Check that tokens have been returned correctly.
+        // Claim the stakes and check that tokens have been returned
+        // correctly.
+        claim_refund(deps.as_mut(), "🦄", 0);
         let res = query(
             deps.as_ref(),
             mock_env(),
@@ -778,7 +977,9 @@ mod tests {
                     address: "🦄".to_string(),
                     amount: Uint128::from(100000u128),
                 }],
+                ..Default::default()
             },
+            ..Default::default()
         };
         let info = mock_info("🦄", &[]);
 
@@ -852,8 +1053,8 @@ mod tests {
         .unwrap();
 
         let res = query(deps.as_ref(), mock_env(), QueryMsg::ListProposals).unwrap();
-        let value: Vec<Proposal> = from_binary(&res).unwrap();
-        assert_eq!(4, value.len());
+        let value: ProposalListResponse = from_binary(&res).unwrap();
+        assert_eq!(4, value.proposals.len());
     }
 
     #[test]
@@ -871,7 +1072,9 @@ mod tests {
                     address: "🦄".to_string(),
                     amount: Uint128::from(100000u128),
                 }],
+                ..Default::default()
             },
+            ..Default::default()
         };
         instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
 
@@ -895,6 +1098,7 @@ mod tests {
             proposal_id: 0,
             position: crate::msg::VotePosition::Yes,
             amount: Uint128::from(100u128),
+            ranking: vec![],
         };
         execute(
             deps.as_mut(),
@@ -924,6 +1128,7 @@ mod tests {
             proposal_id: 1,
             position: crate::msg::VotePosition::Yes,
             amount: Uint128::from(100u128),
+            ranking: vec![],
         };
         execute(
             deps.as_mut(),
@@ -934,7 +1139,7 @@ mod tests {
         .unwrap();
 
         let items = query(deps.as_ref(), mock_env(), QueryMsg::ListItems).unwrap();
-        let items: Vec<DaoItem> = from_binary(&items).unwrap();
+        let items = from_binary::<ItemListResponse>(&items).unwrap().items;
         assert_eq!(items.len(), 2);
         assert_eq!(
             items[0].name,
@@ -946,7 +1151,7 @@ mod tests {
         );
 
         let unicorn = query(deps.as_ref(), mock_env(), QueryMsg::GetItem { item_id: 0 }).unwrap();
-        let unicorn: DaoItem = from_binary(&unicorn).unwrap();
+        let unicorn: ItemResponse = from_binary(&unicorn).unwrap();
         assert_eq!(
             unicorn.name,
             "unicorn emojis must be used for all profile photos"
@@ -973,6 +1178,7 @@ mod tests {
             proposal_id: 2,
             position: crate::msg::VotePosition::Yes,
             amount: Uint128::from(100u128),
+            ranking: vec![],
         };
         execute(
             deps.as_mut(),
@@ -983,7 +1189,7 @@ mod tests {
         .unwrap();
 
         let items = query(deps.as_ref(), mock_env(), QueryMsg::ListItems).unwrap();
-        let items: Vec<DaoItem> = from_binary(&items).unwrap();
+        let items = from_binary::<ItemListResponse>(&items).unwrap().items;
         assert_eq!(items.len(), 1);
         assert_eq!(
             items[0].name,
@@ -995,7 +1201,7 @@ mod tests {
         );
 
         let cow = query(deps.as_ref(), mock_env(), QueryMsg::GetItem { item_id: 0 }).unwrap();
-        let cow: DaoItem = from_binary(&cow).unwrap();
+        let cow: ItemResponse = from_binary(&cow).unwrap();
         assert_eq!(cow.name, "cow emojis must be used for all profile photos");
         assert_eq!(
             cow.contents,
@@ -1021,6 +1227,7 @@ mod tests {
             proposal_id: 3,
             position: crate::msg::VotePosition::Yes,
             amount: Uint128::from(100u128),
+            ranking: vec![],
         };
         execute(
             deps.as_mut(),
@@ -1031,7 +1238,7 @@ mod tests {
         .unwrap();
 
         let res = query(deps.as_ref(), mock_env(), QueryMsg::GetQuorum {}).unwrap();
-        let value: Uint128 = from_binary(&res).unwrap();
+        let value = from_binary::<QuorumResponse>(&res).unwrap().quorum;
         assert_eq!(Uint128::from(1000u128), value);
 
         let proposal = ProposeMsg {
@@ -1054,6 +1261,7 @@ mod tests {
             position: crate::msg::VotePosition::Yes,
             // Need to vote more because the quorum has changed!
             amount: Uint128::from(1000u128),
+            ranking: vec![],
         };
         execute(
             deps.as_mut(),
@@ -1064,7 +1272,9 @@ mod tests {
         .unwrap();
 
         let res = query(deps.as_ref(), mock_env(), QueryMsg::GetProposalCost {}).unwrap();
-        let value: Uint128 = from_binary(&res).unwrap();
+        let value = from_binary::<ProposalCostResponse>(&res)
+            .unwrap()
+            .proposal_cost;
         assert_eq!(Uint128::from(1000u128), value);
     }
 
@@ -1084,7 +1294,9 @@ mod tests {
                     address: "awallet".to_string(),
                     amount: Uint128::from(100000u128),
                 }],
+                ..Default::default()
             },
+            ..Default::default()
         };
         instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
 
@@ -1113,10 +1325,57 @@ mod tests {
         assert_eq!(Uint128::zero(), balance.balance);
     }
 
+    fn claim_refund(deps: DepsMut, voter: &str, proposal_id: u64) {
+        let msg = ExecuteMsg::ClaimRefund { proposal_id };
+        execute(deps, mock_env(), mock_info(voter, &[]), msg).unwrap();
+    }
+
     fn get_balance<T: Into<String>>(deps: Deps, address: T) -> Uint128 {
         tokens::query_balance(deps, address.into()).unwrap().balance
     }
 
+    fn load_proposals(storage: &dyn Storage) -> Vec<Proposal> {
+        proposals()
+            .range(storage, None, None, Order::Ascending)
+            .map(|item| item.unwrap().1)
+            .collect()
+    }
+
+    fn propose(
+        deps: DepsMut,
+        proposer: &str,
+        action: ProposeAction,
+    ) -> Result<Response, ContractError> {
+        let proposal = ProposeMsg {
+            title: "🦄".to_string(),
+            body: "🦄".to_string(),
+            action,
+        };
+        let msg = ExecuteMsg::Propose(proposal);
+        execute(deps, mock_env(), mock_info(proposer, &[]), msg)
+    }
+
+    fn vote(
+        deps: DepsMut,
+        voter: &str,
+        proposal_id: u64,
+        position: crate::msg::VotePosition,
+        amount: u128,
+    ) -> Result<Response, ContractError> {
+        let vote = VoteMsg {
+            proposal_id,
+            position,
+            amount: Uint128::from(amount),
+            ranking: vec![],
+        };
+        execute(
+            deps,
+            mock_env(),
+            mock_info(voter, &[]),
+            ExecuteMsg::Vote(vote),
+        )
+    }
+
     #[test]
     fn token_transfer() {
         let addr1 = String::from("addr0001");
@@ -1139,7 +1398,9 @@ mod tests {
                     address: addr1.clone(),
                     amount: amount1,
                 }],
+                ..Default::default()
             },
+            ..Default::default()
         };
         instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
 
@@ -1217,7 +1478,9 @@ mod tests {
                     address: addr1.clone(),
                     amount: amount1,
                 }],
+                ..Default::default()
             },
+            ..Default::default()
         };
         instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
 
@@ -1306,7 +1569,9 @@ mod tests {
                     address: addr1.clone(),
                     amount: amount1,
                 }],
+                ..Default::default()
             },
+            ..Default::default()
         };
         instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
 
@@ -1354,4 +1619,3396 @@ mod tests {
             remainder
         );
     }
+
+    fn setup_delegation(
+        deps: &mut cosmwasm_std::OwnedDeps<
+            cosmwasm_std::testing::MockStorage,
+            cosmwasm_std::testing::MockApi,
+            cosmwasm_std::testing::MockQuerier,
+            cosmwasm_std::Empty,
+        >,
+        quorum: Uint128,
+    ) {
+        let msg = InstantiateMsg {
+            quorum,
+            proposal_cost: Uint128::from(1u128),
+            token_info: TokenInstantiateInfo {
+                name: "item-dao".to_string(),
+                symbol: "IDAO".to_string(),
+                decimals: 3,
+                initial_balances: vec![
+                    Cw20Coin {
+                        address: "delegate".to_string(),
+                        amount: Uint128::from(100u128),
+                    },
+                    Cw20Coin {
+                        address: "delegator".to_string(),
+                        amount: Uint128::from(100u128),
+                    },
+                ],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("delegator", &[]),
+            ExecuteMsg::Delegate {
+                delegate: "delegate".to_string(),
+            },
+        )
+        .unwrap();
+
+        let proposal = ProposeMsg {
+            title: "🦄!".to_string(),
+            body: "everyone should use a unicorn emoji for their twitter profile!".to_string(),
+            action: ProposeAction::AddItem(DaoItem {
+                name: "unicorn emojis must be used for all profile photos".to_string(),
+                contents: "unicorn emoji shall be defined as being 🦄".to_string(),
+            }),
+        };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("delegate", &[]),
+            ExecuteMsg::Propose(proposal),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn delegation_queries() {
+        let mut deps = mock_dependencies(&[]);
+        setup_delegation(&mut deps, Uint128::from(150u128));
+
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Delegate {
+                address: "delegator".to_string(),
+            },
+        )
+        .unwrap();
+        let value: DelegateResponse = from_binary(&res).unwrap();
+        assert_eq!(value.delegate, Some(Addr::unchecked("delegate")));
+
+        // 99 of the delegate's own tokens as one was spent on the
+        // proposal.
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::VotingPower {
+                address: "delegate".to_string(),
+            },
+        )
+        .unwrap();
+        let value: VotingPowerResponse = from_binary(&res).unwrap();
+        assert_eq!(
+            value,
+            VotingPowerResponse {
+                balance: Uint128::from(99u128),
+                delegated: Uint128::from(100u128),
+                power: Uint128::from(199u128),
+            }
+        );
+
+        // Can't delegate to yourself.
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("delegate", &[]),
+            ExecuteMsg::Delegate {
+                delegate: "delegate".to_string(),
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::SelfDelegation);
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("delegator", &[]),
+            ExecuteMsg::Undelegate,
+        )
+        .unwrap();
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::VotingPower {
+                address: "delegate".to_string(),
+            },
+        )
+        .unwrap();
+        let value: VotingPowerResponse = from_binary(&res).unwrap();
+        assert_eq!(value.power, Uint128::from(99u128));
+
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("delegator", &[]),
+            ExecuteMsg::Undelegate,
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::NotDelegated);
+    }
+
+    #[test]
+    fn delegated_vote_reaches_quorum() {
+        let mut deps = mock_dependencies(&[]);
+        setup_delegation(&mut deps, Uint128::from(105u128));
+
+        // The delegate only stakes 10 tokens but the delegator's 100
+        // are counted as well.
+        vote(
+            deps.as_mut(),
+            "delegate",
+            0,
+            crate::msg::VotePosition::Yes,
+            10,
+        )
+        .unwrap();
+
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GetProposal { proposal_id: 0 },
+        )
+        .unwrap();
+        let prop: ProposalResponse = from_binary(&res).unwrap();
+        assert_eq!(prop.status, ProposalStatus::Passed);
+        assert_eq!(prop.tally.yes, Uint128::from(110u128));
+
+        // Delegated tokens are never moved.
+        claim_refund(deps.as_mut(), "delegate", 0);
+        assert_eq!(
+            get_balance(deps.as_ref(), "delegator"),
+            Uint128::from(100u128)
+        );
+        assert_eq!(
+            get_balance(deps.as_ref(), "delegate"),
+            Uint128::from(100u128)
+        );
+    }
+
+    #[test]
+    fn direct_vote_overrides_delegate() {
+        let mut deps = mock_dependencies(&[]);
+        setup_delegation(&mut deps, Uint128::from(150u128));
+
+        vote(
+            deps.as_mut(),
+            "delegate",
+            0,
+            crate::msg::VotePosition::Yes,
+            10,
+        )
+        .unwrap();
+
+        vote(
+            deps.as_mut(),
+            "delegator",
+            0,
+            crate::msg::VotePosition::No,
+            50,
+        )
+        .unwrap();
+
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GetProposal { proposal_id: 0 },
+        )
+        .unwrap();
+        let prop: ProposalResponse = from_binary(&res).unwrap();
+        assert_eq!(prop.status, ProposalStatus::Pending);
+        assert!(load_proposals(&deps.storage)[0].delegated.is_empty());
+        assert_eq!(prop.tally.yes, Uint128::from(10u128));
+        assert_eq!(prop.tally.no, Uint128::from(50u128));
+
+        // The delegate voting again doesn't pull the delegator's
+        // power back in.
+        vote(
+            deps.as_mut(),
+            "delegate",
+            0,
+            crate::msg::VotePosition::Yes,
+            10,
+        )
+        .unwrap();
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GetProposal { proposal_id: 0 },
+        )
+        .unwrap();
+        let prop: ProposalResponse = from_binary(&res).unwrap();
+        assert_eq!(prop.tally.yes, Uint128::from(20u128));
+    }
+
+    #[test]
+    fn delegated_tokens_are_counted_once() {
+        let mut deps = mock_dependencies(&[]);
+        setup_delegation(&mut deps, Uint128::from(150u128));
+
+        vote(
+            deps.as_mut(),
+            "delegate",
+            0,
+            crate::msg::VotePosition::Yes,
+            10,
+        )
+        .unwrap();
+
+        // The delegator moves the tokens their delegate voted with
+        // and the new holder votes with them.
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("delegator", &[]),
+            ExecuteMsg::Transfer {
+                recipient: "holder".to_string(),
+                amount: Uint128::from(60u128),
+            },
+        )
+        .unwrap();
+        vote(
+            deps.as_mut(),
+            "holder",
+            0,
+            crate::msg::VotePosition::Yes,
+            60,
+        )
+        .unwrap();
+
+        // Only the 40 tokens the delegator still holds count for
+        // the delegate.
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GetProposal { proposal_id: 0 },
+        )
+        .unwrap();
+        let prop: ProposalResponse = from_binary(&res).unwrap();
+        assert_eq!(prop.status, ProposalStatus::Pending);
+        assert_eq!(prop.tally.yes, Uint128::from(110u128));
+    }
+
+    #[test]
+    fn delegate_without_tokens() {
+        let mut deps = mock_dependencies(&[]);
+        setup_delegation(&mut deps, Uint128::from(150u128));
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("delegate", &[]),
+            ExecuteMsg::Transfer {
+                recipient: "holder".to_string(),
+                amount: Uint128::from(99u128),
+            },
+        )
+        .unwrap();
+        assert_eq!(get_balance(deps.as_ref(), "delegate"), Uint128::zero());
+
+        // Without delegated power there is nothing to vote with.
+        let err = vote(deps.as_mut(), "holder", 0, crate::msg::VotePosition::Yes, 0).unwrap_err();
+        assert_eq!(err, ContractError::InvalidZeroAmount);
+
+        // The delegate votes with only the power delegated to them.
+        vote(
+            deps.as_mut(),
+            "delegate",
+            0,
+            crate::msg::VotePosition::Yes,
+            0,
+        )
+        .unwrap();
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GetProposal { proposal_id: 0 },
+        )
+        .unwrap();
+        let prop: ProposalResponse = from_binary(&res).unwrap();
+        assert_eq!(prop.tally.yes, Uint128::from(100u128));
+        let proposal = &load_proposals(&deps.storage)[0];
+        assert!(!proposal.has_direct_vote(&Addr::unchecked("delegate")));
+        assert_eq!(get_balance(deps.as_ref(), "delegate"), Uint128::zero());
+
+        // The delegator's power can't be used twice.
+        let err = vote(
+            deps.as_mut(),
+            "delegate",
+            0,
+            crate::msg::VotePosition::Yes,
+            0,
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidZeroAmount);
+    }
+
+    #[test]
+    fn delegated_votes_are_dropped() {
+        let mut deps = mock_dependencies(&[]);
+        setup_delegation(&mut deps, Uint128::from(150u128));
+
+        let yes = |deps: Deps| {
+            let res = query(deps, mock_env(), QueryMsg::GetProposal { proposal_id: 0 }).unwrap();
+            from_binary::<ProposalResponse>(&res).unwrap().tally.yes
+        };
+
+        // Withdrawing the delegate's own stake drops the votes they
+        // cast for their delegators too.
+        vote(
+            deps.as_mut(),
+            "delegate",
+            0,
+            crate::msg::VotePosition::Yes,
+            10,
+        )
+        .unwrap();
+        assert_eq!(yes(deps.as_ref()), Uint128::from(110u128));
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("delegate", &[]),
+            ExecuteMsg::Withdraw(WithdrawVoteMsg { proposal_id: 0 }),
+        )
+        .unwrap();
+        assert_eq!(yes(deps.as_ref()), Uint128::zero());
+
+        // Undelegating drops the votes already cast with the
+        // delegator's power.
+        vote(
+            deps.as_mut(),
+            "delegate",
+            0,
+            crate::msg::VotePosition::Yes,
+            10,
+        )
+        .unwrap();
+        assert_eq!(yes(deps.as_ref()), Uint128::from(110u128));
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("delegator", &[]),
+            ExecuteMsg::Undelegate,
+        )
+        .unwrap();
+        assert_eq!(yes(deps.as_ref()), Uint128::from(10u128));
+        assert!(load_proposals(&deps.storage)[0].delegated.is_empty());
+    }
+
+    #[test]
+    fn withdrawn_delegator_is_represented() {
+        let mut deps = mock_dependencies(&[]);
+        setup_delegation(&mut deps, Uint128::from(150u128));
+
+        // The delegator votes and changes their mind. Their delegate
+        // may then vote for them.
+        vote(
+            deps.as_mut(),
+            "delegator",
+            0,
+            crate::msg::VotePosition::No,
+            10,
+        )
+        .unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("delegator", &[]),
+            ExecuteMsg::Withdraw(WithdrawVoteMsg { proposal_id: 0 }),
+        )
+        .unwrap();
+        vote(
+            deps.as_mut(),
+            "delegate",
+            0,
+            crate::msg::VotePosition::Yes,
+            10,
+        )
+        .unwrap();
+
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GetProposal { proposal_id: 0 },
+        )
+        .unwrap();
+        let prop: ProposalResponse = from_binary(&res).unwrap();
+        assert_eq!(prop.tally.yes, Uint128::from(110u128));
+        assert_eq!(prop.tally.no, Uint128::zero());
+    }
+
+    #[test]
+    fn redelegate_during_vote() {
+        let mut deps = mock_dependencies(&[]);
+        setup_delegation(&mut deps, Uint128::from(150u128));
+
+        let yes = |deps: Deps| {
+            let res = query(deps, mock_env(), QueryMsg::GetProposal { proposal_id: 0 }).unwrap();
+            from_binary::<ProposalResponse>(&res).unwrap().tally.yes
+        };
+        vote(
+            deps.as_mut(),
+            "delegate",
+            0,
+            crate::msg::VotePosition::Yes,
+            10,
+        )
+        .unwrap();
+        assert_eq!(yes(deps.as_ref()), Uint128::from(110u128));
+
+        // Moving to a new delegate takes the delegator's power away
+        // from the votes the old one cast so it is only counted once.
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("delegator", &[]),
+            ExecuteMsg::Delegate {
+                delegate: "other".to_string(),
+            },
+        )
+        .unwrap();
+        assert_eq!(yes(deps.as_ref()), Uint128::from(10u128));
+        vote(deps.as_mut(), "other", 0, crate::msg::VotePosition::Yes, 0).unwrap();
+        assert_eq!(yes(deps.as_ref()), Uint128::from(110u128));
+        let proposal = &load_proposals(&deps.storage)[0];
+        assert_eq!(proposal.delegated.len(), 1);
+        assert_eq!(proposal.delegated[0].delegate, Addr::unchecked("other"));
+    }
+
+    #[test]
+    fn unvested_tokens_are_not_delegated() {
+        let mut deps = mock_dependencies(&[]);
+        let height = mock_env().block.height;
+
+        let msg = InstantiateMsg {
+            quorum: Uint128::from(600u128),
+            proposal_cost: Uint128::from(1u128),
+            token_info: TokenInstantiateInfo {
+                name: "item-dao".to_string(),
+                symbol: "IDAO".to_string(),
+                decimals: 3,
+                initial_balances: vec![Cw20Coin {
+                    address: "delegate".to_string(),
+                    amount: Uint128::from(100u128),
+                }],
+                vesting: vec![VestingGrant {
+                    address: "delegator".to_string(),
+                    schedule: VestingSchedule {
+                        amount: Uint128::from(1000u128),
+                        unit: VestingUnit::Height,
+                        start: height,
+                        cliff: height + 10,
+                        end: height + 100,
+                    },
+                }],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("delegator", &[]),
+            ExecuteMsg::Delegate {
+                delegate: "delegate".to_string(),
+            },
+        )
+        .unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("delegate", &[]),
+            ExecuteMsg::Propose(ProposeMsg {
+                title: "🦄".to_string(),
+                body: "🦄".to_string(),
+                action: ProposeAction::RemoveItem { id: 0 },
+            }),
+        )
+        .unwrap();
+
+        // Halfway through the schedule only the vested half counts.
+        let mut env = mock_env();
+        env.block.height += 50;
+        let vote = VoteMsg {
+            proposal_id: 0,
+            position: crate::msg::VotePosition::Yes,
+            amount: Uint128::from(10u128),
+            ranking: vec![],
+        };
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("delegate", &[]),
+            ExecuteMsg::Vote(vote),
+        )
+        .unwrap();
+        let res = query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::GetProposal { proposal_id: 0 },
+        )
+        .unwrap();
+        let prop: ProposalResponse = from_binary(&res).unwrap();
+        assert_eq!(prop.status, ProposalStatus::Pending);
+        assert_eq!(prop.tally.yes, Uint128::from(510u128));
+
+        // The query agrees with what the vote counted.
+        let res = query(
+            deps.as_ref(),
+            env,
+            QueryMsg::VotingPower {
+                address: "delegate".to_string(),
+            },
+        )
+        .unwrap();
+        let power: VotingPowerResponse = from_binary(&res).unwrap();
+        assert_eq!(power.delegated, Uint128::from(500u128));
+    }
+
+    #[test]
+    fn vesting_locks_tokens() {
+        let mut deps = mock_dependencies(&[]);
+        let height = mock_env().block.height;
+
+        let msg = InstantiateMsg {
+            quorum: Uint128::from(10u128),
+            proposal_cost: Uint128::from(1u128),
+            token_info: TokenInstantiateInfo {
+                name: "item-dao".to_string(),
+                symbol: "IDAO".to_string(),
+                decimals: 3,
+                initial_balances: vec![],
+                vesting: vec![VestingGrant {
+                    address: "vester".to_string(),
+                    schedule: VestingSchedule {
+                        amount: Uint128::from(1000u128),
+                        unit: VestingUnit::Height,
+                        start: height,
+                        cliff: height + 10,
+                        end: height + 100,
+                    },
+                }],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        assert_eq!(
+            tokens::query_token_info(deps.as_ref())
+                .unwrap()
+                .total_supply,
+            Uint128::from(1000u128)
+        );
+        assert_eq!(
+            get_balance(deps.as_ref(), "vester"),
+            Uint128::from(1000u128)
+        );
+
+        let transfer = |amount: u128| ExecuteMsg::Transfer {
+            recipient: "friend".to_string(),
+            amount: Uint128::from(amount),
+        };
+
+        // Nothing can be moved before the cliff.
+        let mut env = mock_env();
+        env.block.height += 5;
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("vester", &[]),
+            transfer(1),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::TokensLocked {
+                available: Uint128::zero(),
+                needed: Uint128::from(1u128),
+            }
+        );
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("vester", &[]),
+            ExecuteMsg::Burn {
+                amount: Uint128::from(1u128),
+            },
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::TokensLocked { .. }));
+
+        // Or used to pay for proposals.
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("vester", &[]),
+            ExecuteMsg::Propose(ProposeMsg {
+                title: "🦄!".to_string(),
+                body: "everyone should use a unicorn emoji for their twitter profile!".to_string(),
+                action: ProposeAction::RemoveItem { id: 0 },
+            }),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::TokensLocked { .. }));
+
+        // Halfway through the schedule half of the tokens are free.
+        env.block.height += 45;
+        let res = query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::VestingInfo {
+                address: "vester".to_string(),
+            },
+        )
+        .unwrap();
+        let info: VestingInfoResponse = from_binary(&res).unwrap();
+        assert_eq!(info.vested, Uint128::from(500u128));
+        assert_eq!(info.locked, Uint128::from(500u128));
+
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("vester", &[]),
+            transfer(500),
+        )
+        .unwrap();
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("vester", &[]),
+            transfer(1),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::TokensLocked { .. }));
+
+        // Everything is free once the schedule ends.
+        env.block.height += 50;
+        execute(deps.as_mut(), env, mock_info("vester", &[]), transfer(500)).unwrap();
+        assert_eq!(get_balance(deps.as_ref(), "vester"), Uint128::zero());
+        assert_eq!(
+            get_balance(deps.as_ref(), "friend"),
+            Uint128::from(1000u128)
+        );
+    }
+
+    #[test]
+    fn create_vesting_proposal() {
+        let mut deps = mock_dependencies(&[]);
+        let info = mock_info("🦄", &[]);
+        let now = mock_env().block.time.seconds();
+
+        let msg = InstantiateMsg {
+            quorum: Uint128::from(10u128),
+            proposal_cost: Uint128::from(1u128),
+            token_info: TokenInstantiateInfo {
+                name: "item-dao".to_string(),
+                symbol: "IDAO".to_string(),
+                decimals: 3,
+                initial_balances: vec![Cw20Coin {
+                    address: "🦄".to_string(),
+                    amount: Uint128::from(100u128),
+                }],
+                ..Default::default()
+            },
+            locked_tokens_can_vote: true,
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        // Invalid schedules are rejected when proposed.
+        let mut schedule = VestingSchedule {
+            amount: Uint128::from(50u128),
+            unit: VestingUnit::Time,
+            start: now + 100,
+            cliff: now,
+            end: now + 1000,
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::Propose(ProposeMsg {
+                title: "grant".to_string(),
+                body: "grant some tokens to a new member".to_string(),
+                action: ProposeAction::CreateVesting {
+                    address: "newbie".to_string(),
+                    schedule: schedule.clone(),
+                },
+            }),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::InvalidVestingSchedule { .. }));
+
+        schedule.start = now;
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::Propose(ProposeMsg {
+                title: "grant".to_string(),
+                body: "grant some tokens to a new member".to_string(),
+                action: ProposeAction::CreateVesting {
+                    address: "newbie".to_string(),
+                    schedule,
+                },
+            }),
+        )
+        .unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::Vote(VoteMsg {
+                proposal_id: 0,
+                position: crate::msg::VotePosition::Yes,
+                amount: Uint128::from(10u128),
+                ranking: vec![],
+            }),
+        )
+        .unwrap();
+
+        assert_eq!(get_balance(deps.as_ref(), "newbie"), Uint128::from(50u128));
+        assert_eq!(
+            tokens::query_token_info(deps.as_ref())
+                .unwrap()
+                .total_supply,
+            Uint128::from(150u128)
+        );
+
+        // The new tokens are locked but this DAO lets them vote.
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("newbie", &[]),
+            ExecuteMsg::Transfer {
+                recipient: "🦄".to_string(),
+                amount: Uint128::from(1u128),
+            },
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::TokensLocked { .. }));
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("🦄", &[]),
+            ExecuteMsg::Propose(ProposeMsg {
+                title: "🦄!".to_string(),
+                body: "everyone should use a unicorn emoji for their twitter profile!".to_string(),
+                action: ProposeAction::RemoveItem { id: 0 },
+            }),
+        )
+        .unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("newbie", &[]),
+            ExecuteMsg::Vote(VoteMsg {
+                proposal_id: 1,
+                position: crate::msg::VotePosition::No,
+                amount: Uint128::from(5u128),
+                ranking: vec![],
+            }),
+        )
+        .unwrap();
+        assert_eq!(get_balance(deps.as_ref(), "newbie"), Uint128::from(45u128));
+    }
+
+    #[test]
+    fn non_transferable_tokens() {
+        let mut deps = mock_dependencies(&[]);
+        let info = mock_info("🦄", &[]);
+
+        let msg = InstantiateMsg {
+            quorum: Uint128::from(10u128),
+            proposal_cost: Uint128::from(1u128),
+            token_info: TokenInstantiateInfo {
+                name: "item-dao".to_string(),
+                symbol: "IDAO".to_string(),
+                decimals: 3,
+                initial_balances: vec![Cw20Coin {
+                    address: "🦄".to_string(),
+                    amount: Uint128::from(100u128),
+                }],
+                mode: TokenMode::NonTransferable,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::Transfer {
+                recipient: "friend".to_string(),
+                amount: Uint128::from(1u128),
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::TransfersDisabled);
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::Send {
+                contract: "friend".to_string(),
+                amount: Uint128::from(1u128),
+                msg: Binary::from(r#"{"some":123}"#.as_bytes()),
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::TransfersDisabled);
+
+        // Members can still leave.
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::Burn {
+                amount: Uint128::from(1u128),
+            },
+        )
+        .unwrap();
+
+        // Staking and refunds still work.
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::Propose(ProposeMsg {
+                title: "pause".to_string(),
+                body: "stop all token movement for a while".to_string(),
+                action: ProposeAction::ChangeTokenMode {
+                    mode: TokenMode::Paused,
+                },
+            }),
+        )
+        .unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::Vote(VoteMsg {
+                proposal_id: 0,
+                position: crate::msg::VotePosition::Yes,
+                amount: Uint128::from(10u128),
+                ranking: vec![],
+            }),
+        )
+        .unwrap();
+        claim_refund(deps.as_mut(), "🦄", 0);
+        assert_eq!(get_balance(deps.as_ref(), "🦄"), Uint128::from(99u128));
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::TokenMode).unwrap();
+        let mode = from_binary::<TokenModeResponse>(&res).unwrap().mode;
+        assert_eq!(mode, TokenMode::Paused);
+
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::Burn {
+                amount: Uint128::from(1u128),
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::TransfersDisabled);
+    }
+
+    #[test]
+    fn stakes_are_kept_out_of_treasury() {
+        let mut deps = mock_dependencies(&[]);
+        let info = mock_info("🦄", &[]);
+
+        setup_near_pass(&mut deps, info.clone());
+
+        // A donation to the DAO goes to the treasury.
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::Transfer {
+                recipient: MOCK_CONTRACT_ADDR.to_string(),
+                amount: Uint128::from(50u128),
+            },
+        )
+        .unwrap();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::TreasuryBalance).unwrap();
+        let treasury: BalanceResponse = from_binary(&res).unwrap();
+        assert_eq!(treasury.balance, Uint128::from(50u128));
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::Vote(VoteMsg {
+                proposal_id: 0,
+                position: crate::msg::VotePosition::Yes,
+                amount: Uint128::from(1u128),
+                ranking: vec![],
+            }),
+        )
+        .unwrap();
+
+        // All stakes are returned and the treasury is untouched.
+        claim_refund(deps.as_mut(), "🦄", 0);
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::StakedBalance {
+                address: "🦄".to_string(),
+            },
+        )
+        .unwrap();
+        let staked: BalanceResponse = from_binary(&res).unwrap();
+        assert_eq!(staked.balance, Uint128::zero());
+        assert_eq!(get_balance(deps.as_ref(), "🦄"), Uint128::from(99950u128));
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::TreasuryBalance).unwrap();
+        let treasury: BalanceResponse = from_binary(&res).unwrap();
+        assert_eq!(treasury.balance, Uint128::from(50u128));
+    }
+
+    #[test]
+    fn treasury_proposals() {
+        let mut deps = mock_dependencies(&[]);
+        let info = mock_info("🦄", &[]);
+
+        let msg = InstantiateMsg {
+            quorum: Uint128::from(10u128),
+            proposal_cost: Uint128::from(1u128),
+            token_info: TokenInstantiateInfo {
+                name: "item-dao".to_string(),
+                symbol: "IDAO".to_string(),
+                decimals: 3,
+                initial_balances: vec![Cw20Coin {
+                    address: "🦄".to_string(),
+                    amount: Uint128::from(1000u128),
+                }],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::Transfer {
+                recipient: MOCK_CONTRACT_ADDR.to_string(),
+                amount: Uint128::from(50u128),
+            },
+        )
+        .unwrap();
+
+        // Can't spend more than the treasury holds even though
+        // there are more tokens than that staked on the proposal.
+        propose(
+            deps.as_mut(),
+            "🦄",
+            ProposeAction::TreasuryTransfer {
+                recipient: "friend".to_string(),
+                amount: Uint128::from(60u128),
+            },
+        )
+        .unwrap();
+        let err = vote(deps.as_mut(), "🦄", 0, crate::msg::VotePosition::Yes, 100).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::InsufficientTreasury {
+                needed: Uint128::from(60u128),
+                available: Uint128::from(50u128),
+            }
+        );
+
+        propose(
+            deps.as_mut(),
+            "🦄",
+            ProposeAction::TreasuryTransfer {
+                recipient: "friend".to_string(),
+                amount: Uint128::from(20u128),
+            },
+        )
+        .unwrap();
+        vote(deps.as_mut(), "🦄", 1, crate::msg::VotePosition::Yes, 100).unwrap();
+        assert_eq!(get_balance(deps.as_ref(), "friend"), Uint128::from(20u128));
+
+        let send_msg = Binary::from(r#"{"some":123}"#.as_bytes());
+        propose(
+            deps.as_mut(),
+            "🦄",
+            ProposeAction::TreasurySend {
+                contract: "market".to_string(),
+                amount: Uint128::from(30u128),
+                msg: send_msg.clone(),
+            },
+        )
+        .unwrap();
+        let res = vote(deps.as_mut(), "🦄", 2, crate::msg::VotePosition::Yes, 100).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "market".to_string(),
+                msg: Cw20ReceiveMsg {
+                    sender: MOCK_CONTRACT_ADDR.to_string(),
+                    amount: Uint128::from(30u128),
+                    msg: send_msg,
+                }
+                .into_binary()
+                .unwrap(),
+                funds: vec![],
+            }))]
+        );
+        assert_eq!(get_balance(deps.as_ref(), "market"), Uint128::from(30u128));
+        assert_eq!(
+            get_balance(deps.as_ref(), MOCK_CONTRACT_ADDR),
+            Uint128::zero()
+        );
+    }
+
+    #[test]
+    fn native_treasury() {
+        let mut deps = mock_dependencies(&coins(1000, "ujuno"));
+        let info = mock_info("🦄", &[]);
+
+        let msg = InstantiateMsg {
+            quorum: Uint128::from(10u128),
+            proposal_cost: Uint128::from(1u128),
+            native_proposal_cost: Some(coin(10, "ujuno")),
+            token_info: TokenInstantiateInfo {
+                name: "item-dao".to_string(),
+                symbol: "IDAO".to_string(),
+                decimals: 3,
+                initial_balances: vec![Cw20Coin {
+                    address: "🦄".to_string(),
+                    amount: Uint128::from(100u128),
+                }],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        let proposal = ProposeMsg {
+            title: "pay".to_string(),
+            body: "pay our friend for their work".to_string(),
+            action: ProposeAction::BankSend {
+                to: "friend".to_string(),
+                amount: coins(500, "ujuno"),
+            },
+        };
+
+        // Only the configured denom is accepted.
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("🦄", &coins(10, "uatom")),
+            ExecuteMsg::Propose(proposal.clone()),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::Payment(PaymentError::MissingDenom("ujuno".to_string()))
+        );
+
+        // The bank module moves funds before the contract executes.
+        deps.querier
+            .update_balance(MOCK_CONTRACT_ADDR, coins(1010, "ujuno"));
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("🦄", &coins(10, "ujuno")),
+            ExecuteMsg::Propose(proposal),
+        )
+        .unwrap();
+        // The deposit was paid natively so no tokens were staked.
+        assert_eq!(get_balance(deps.as_ref(), "🦄"), Uint128::from(100u128));
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::TreasuryBalances).unwrap();
+        let treasury: TreasuryBalancesResponse = from_binary(&res).unwrap();
+        assert_eq!(treasury.balances, coins(1000, "ujuno"));
+
+        // Votes don't accept funds.
+        let vote = VoteMsg {
+            proposal_id: 0,
+            position: crate::msg::VotePosition::Yes,
+            amount: Uint128::from(10u128),
+            ranking: vec![],
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("🦄", &coins(10, "ujuno")),
+            ExecuteMsg::Vote(vote.clone()),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Payment(PaymentError::NonPayable {}));
+
+        let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Vote(vote)).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![
+                SubMsg::new(BankMsg::Send {
+                    to_address: "🦄".to_string(),
+                    amount: coins(10, "ujuno"),
+                }),
+                SubMsg::new(BankMsg::Send {
+                    to_address: "friend".to_string(),
+                    amount: coins(500, "ujuno"),
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn deposit_policy() {
+        let mut deps = mock_dependencies(&[]);
+        let info = mock_info("🦄", &[]);
+
+        let msg = InstantiateMsg {
+            quorum: Uint128::from(10u128),
+            proposal_cost: Uint128::from(5u128),
+            token_info: TokenInstantiateInfo {
+                name: "item-dao".to_string(),
+                symbol: "IDAO".to_string(),
+                decimals: 3,
+                initial_balances: vec![Cw20Coin {
+                    address: "🦄".to_string(),
+                    amount: Uint128::from(100u128),
+                }],
+                ..Default::default()
+            },
+            deposit_policy: DepositPolicy {
+                refund: DepositRefund::OnPass,
+                forfeit: DepositForfeit::Treasury,
+            },
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        // A rejected proposal's deposit goes to the treasury.
+        propose(deps.as_mut(), "🦄", ProposeAction::RemoveItem { id: 0 }).unwrap();
+        vote(deps.as_mut(), "🦄", 0, crate::msg::VotePosition::No, 10).unwrap();
+        claim_refund(deps.as_mut(), "🦄", 0);
+        assert_eq!(get_balance(deps.as_ref(), "🦄"), Uint128::from(95u128));
+        assert_eq!(
+            get_balance(deps.as_ref(), MOCK_CONTRACT_ADDR),
+            Uint128::from(5u128)
+        );
+
+        // A passed proposal's deposit is refunded.
+        propose(
+            deps.as_mut(),
+            "🦄",
+            ProposeAction::ChangeDepositPolicy {
+                policy: DepositPolicy {
+                    refund: DepositRefund::OnPass,
+                    forfeit: DepositForfeit::Burn,
+                },
+            },
+        )
+        .unwrap();
+        vote(deps.as_mut(), "🦄", 1, crate::msg::VotePosition::Yes, 10).unwrap();
+        claim_refund(deps.as_mut(), "🦄", 1);
+        assert_eq!(get_balance(deps.as_ref(), "🦄"), Uint128::from(95u128));
+
+        // Under the new policy rejected deposits are burned.
+        propose(deps.as_mut(), "🦄", ProposeAction::RemoveItem { id: 0 }).unwrap();
+        vote(deps.as_mut(), "🦄", 2, crate::msg::VotePosition::No, 10).unwrap();
+        claim_refund(deps.as_mut(), "🦄", 2);
+
+        assert_eq!(get_balance(deps.as_ref(), "🦄"), Uint128::from(90u128));
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::TokenInfo).unwrap();
+        let token_info: TokenInfoResponse = from_binary(&res).unwrap();
+        assert_eq!(token_info.total_supply, Uint128::from(95u128));
+
+        // Under `OnQuorum` a proposal that only reaches quorum
+        // through abstentions loses its deposit where `Always` would
+        // refund it.
+        propose(
+            deps.as_mut(),
+            "🦄",
+            ProposeAction::ChangeDepositPolicy {
+                policy: DepositPolicy {
+                    refund: DepositRefund::OnQuorum,
+                    forfeit: DepositForfeit::Treasury,
+                },
+            },
+        )
+        .unwrap();
+        vote(deps.as_mut(), "🦄", 3, crate::msg::VotePosition::Yes, 10).unwrap();
+        claim_refund(deps.as_mut(), "🦄", 3);
+        propose(deps.as_mut(), "🦄", ProposeAction::RemoveItem { id: 0 }).unwrap();
+        vote(
+            deps.as_mut(),
+            "🦄",
+            4,
+            crate::msg::VotePosition::Abstain,
+            10,
+        )
+        .unwrap();
+        claim_refund(deps.as_mut(), "🦄", 4);
+        assert_eq!(get_balance(deps.as_ref(), "🦄"), Uint128::from(85u128));
+        assert_eq!(
+            get_balance(deps.as_ref(), MOCK_CONTRACT_ADDR),
+            Uint128::from(10u128)
+        );
+
+        // A rejected proposal that reached quorum is refunded.
+        propose(deps.as_mut(), "🦄", ProposeAction::RemoveItem { id: 0 }).unwrap();
+        vote(deps.as_mut(), "🦄", 5, crate::msg::VotePosition::No, 10).unwrap();
+        claim_refund(deps.as_mut(), "🦄", 5);
+        assert_eq!(get_balance(deps.as_ref(), "🦄"), Uint128::from(85u128));
+    }
+
+    #[test]
+    fn veto() {
+        let mut deps = mock_dependencies(&[]);
+
+        let msg = InstantiateMsg {
+            quorum: Uint128::from(100u128),
+            proposal_cost: Uint128::from(5u128),
+            token_info: TokenInstantiateInfo {
+                name: "item-dao".to_string(),
+                symbol: "IDAO".to_string(),
+                decimals: 3,
+                initial_balances: vec![
+                    Cw20Coin {
+                        address: "🦄".to_string(),
+                        amount: Uint128::from(100u128),
+                    },
+                    Cw20Coin {
+                        address: "🐴".to_string(),
+                        amount: Uint128::from(100u128),
+                    },
+                ],
+                ..Default::default()
+            },
+            veto_threshold: Some(Decimal::percent(33)),
+            ..Default::default()
+        };
+        let err = instantiate(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("🦄", &[]),
+            InstantiateMsg {
+                veto_threshold: Some(Decimal::percent(101)),
+                ..msg.clone()
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidVetoThreshold);
+        instantiate(deps.as_mut(), mock_env(), mock_info("🦄", &[]), msg).unwrap();
+
+        let proposal = ProposeMsg {
+            title: "🦄".to_string(),
+            body: "replace all of the horses with unicorns".to_string(),
+            action: ProposeAction::RemoveItem { id: 0 },
+        };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("🦄", &[]),
+            ExecuteMsg::Propose(proposal),
+        )
+        .unwrap();
+
+        for (voter, position, amount) in [
+            ("🦄", crate::msg::VotePosition::Yes, 60u128),
+            ("🐴", crate::msg::VotePosition::NoWithVeto, 40u128),
+        ] {
+            vote(deps.as_mut(), voter, 0, position, amount).unwrap();
+        }
+
+        // Yes beat no but the veto fails the proposal anyway and
+        // the deposit is forfeited.
+        claim_refund(deps.as_mut(), "🦄", 0);
+        claim_refund(deps.as_mut(), "🐴", 0);
+        let proposals = load_proposals(&deps.storage);
+        assert_eq!(proposals[0].status, ProposalStatus::Failed);
+        assert_eq!(get_balance(deps.as_ref(), "🦄"), Uint128::from(95u128));
+        assert_eq!(get_balance(deps.as_ref(), "🐴"), Uint128::from(100u128));
+        assert_eq!(
+            get_balance(deps.as_ref(), MOCK_CONTRACT_ADDR),
+            Uint128::from(5u128)
+        );
+    }
+
+    #[test]
+    fn weighted_vote() {
+        let mut deps = mock_dependencies(&[]);
+        let info = mock_info("delegate", &[]);
+        setup_delegation(&mut deps, Uint128::from(200u128));
+
+        let weighted =
+            |options: Vec<(crate::msg::VotePosition, Decimal)>| ExecuteMsg::VoteWeighted {
+                proposal_id: 0,
+                amount: Uint128::from(7u128),
+                options,
+            };
+
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            weighted(vec![
+                (crate::msg::VotePosition::Yes, Decimal::percent(50)),
+                (crate::msg::VotePosition::No, Decimal::percent(40)),
+            ]),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidVoteWeights);
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            weighted(vec![
+                (crate::msg::VotePosition::Yes, Decimal::percent(50)),
+                (crate::msg::VotePosition::Yes, Decimal::percent(50)),
+            ]),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidVoteWeights);
+        let huge = Decimal::from_ratio(u128::MAX, 1_000_000_000_000_000_000u128);
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            weighted(vec![
+                (crate::msg::VotePosition::Yes, huge),
+                (crate::msg::VotePosition::No, huge),
+            ]),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidVoteWeights);
+
+        // The rounding remainder goes to the first position.
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            weighted(vec![
+                (crate::msg::VotePosition::Yes, Decimal::percent(50)),
+                (crate::msg::VotePosition::No, Decimal::percent(50)),
+            ]),
+        )
+        .unwrap();
+
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GetVote {
+                proposal_id: 0,
+                voter: "delegate".to_string(),
+            },
+        )
+        .unwrap();
+        let vote: VoteResponse = from_binary(&res).unwrap();
+        assert_eq!(
+            vote.votes,
+            vec![
+                (crate::msg::VotePosition::Yes, Uint128::from(4u128)),
+                (crate::msg::VotePosition::No, Uint128::from(3u128)),
+            ]
+        );
+
+        // The delegator's power is split in the same way.
+        let proposals = load_proposals(&deps.storage);
+        assert_eq!(proposals[0].status, ProposalStatus::Pending);
+        assert_eq!(
+            proposals[0].get_votes(crate::msg::VotePosition::Yes),
+            Uint128::from(54u128)
+        );
+        assert_eq!(
+            proposals[0].get_votes(crate::msg::VotePosition::No),
+            Uint128::from(53u128)
+        );
+    }
+
+    #[test]
+    fn isqrt() {
+        use crate::quadratic::isqrt;
+        for (n, root) in [(0u128, 0u128), (1, 1), (3, 1), (4, 2), (80, 8), (81, 9)] {
+            assert_eq!(isqrt(Uint128::from(n)), Uint128::from(root));
+        }
+        assert_eq!(isqrt(Uint128::MAX), Uint128::from(u64::MAX));
+    }
+
+    #[test]
+    fn quadratic_voting() {
+        let mut deps = mock_dependencies(&[]);
+        let fish = ["🐟", "🐠", "🐡", "🦈"];
+
+        let mut initial_balances: Vec<_> = fish
+            .iter()
+            .map(|f| Cw20Coin {
+                address: f.to_string(),
+                amount: Uint128::from(10u128),
+            })
+            .collect();
+        initial_balances.push(Cw20Coin {
+            address: "🐳".to_string(),
+            amount: Uint128::from(100u128),
+        });
+        let msg = InstantiateMsg {
+            quorum: Uint128::from(112u128),
+            token_info: TokenInstantiateInfo {
+                name: "item-dao".to_string(),
+                symbol: "IDAO".to_string(),
+                decimals: 3,
+                initial_balances,
+                ..Default::default()
+            },
+            quadratic: true,
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("🐳", &[]), msg).unwrap();
+
+        let proposal = ProposeMsg {
+            title: "🐳".to_string(),
+            body: "the ocean belongs to the whales".to_string(),
+            action: ProposeAction::RemoveItem { id: 0 },
+        };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("🐳", &[]),
+            ExecuteMsg::Propose(proposal),
+        )
+        .unwrap();
+
+        // The whale splits their stake to try and get more weight
+        // than the square root of the total.
+        let votes = [
+            ("🐳", crate::msg::VotePosition::Yes, 40u128),
+            ("🐳", crate::msg::VotePosition::Yes, 41u128),
+            ("🐟", crate::msg::VotePosition::No, 9u128),
+            ("🐠", crate::msg::VotePosition::No, 9u128),
+            ("🐡", crate::msg::VotePosition::No, 9u128),
+            ("🦈", crate::msg::VotePosition::No, 4u128),
+        ];
+        for (voter, position, amount) in votes {
+            vote(deps.as_mut(), voter, 0, position, amount).unwrap();
+        }
+
+        let proposals = load_proposals(&deps.storage);
+        let proposal = &proposals[0];
+        assert_eq!(
+            proposal.get_weighted_votes(crate::msg::VotePosition::Yes),
+            Ok(Uint128::from(9u128))
+        );
+        assert_eq!(
+            proposal.get_weighted_votes(crate::msg::VotePosition::No),
+            Ok(Uint128::from(11u128))
+        );
+        // 81 tokens beat 31 but the fish win on weight.
+        assert_eq!(proposal.status, ProposalStatus::Failed);
+    }
+
+    #[test]
+    fn quadratic_choice() {
+        let mut deps = mock_dependencies(&[]);
+        let balances = [("🐳", 100u128), ("🐟", 16u128), ("🐠", 49u128)];
+
+        let msg = InstantiateMsg {
+            quorum: Uint128::from(165u128),
+            token_info: TokenInstantiateInfo {
+                name: "item-dao".to_string(),
+                symbol: "IDAO".to_string(),
+                decimals: 3,
+                initial_balances: balances
+                    .iter()
+                    .map(|(address, amount)| Cw20Coin {
+                        address: address.to_string(),
+                        amount: Uint128::from(*amount),
+                    })
+                    .collect(),
+                ..Default::default()
+            },
+            quadratic: true,
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("🐳", &[]), msg).unwrap();
+
+        let choices = ["whales", "fish"]
+            .iter()
+            .map(|title| Choice {
+                title: title.to_string(),
+                action: None,
+            })
+            .collect();
+        propose(
+            deps.as_mut(),
+            "🐳",
+            ProposeAction::MultipleChoice { choices },
+        )
+        .unwrap();
+
+        for (voter, amount) in balances {
+            let ranking = if voter == "🐳" { vec![0] } else { vec![1] };
+            let vote = VoteMsg {
+                proposal_id: 0,
+                position: crate::msg::VotePosition::Yes,
+                amount: Uint128::from(amount),
+                ranking,
+            };
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info(voter, &[]),
+                ExecuteMsg::Vote(vote),
+            )
+            .unwrap();
+        }
+
+        // 100 tokens beat 65 but the fish carry 4 + 7 votes to the
+        // whale's 10.
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::ChoiceTally { proposal_id: 0 },
+        )
+        .unwrap();
+        let tally: ChoiceTallyResponse = from_binary(&res).unwrap();
+        assert_eq!(
+            tally,
+            ChoiceTallyResponse {
+                first_choices: vec![Uint128::from(10u128), Uint128::from(11u128)],
+                winner: Some(1),
+            }
+        );
+        assert_eq!(load_proposals(&deps.storage)[0].winner, Some(1));
+    }
+
+    #[test]
+    fn conviction_grant() {
+        let mut deps = mock_dependencies(&[]);
+        let info = mock_info("🦄", &[]);
+
+        let msg = InstantiateMsg {
+            quorum: Uint128::from(10u128),
+            token_info: TokenInstantiateInfo {
+                name: "item-dao".to_string(),
+                symbol: "IDAO".to_string(),
+                decimals: 3,
+                initial_balances: vec![
+                    Cw20Coin {
+                        address: "🦄".to_string(),
+                        amount: Uint128::from(1000u128),
+                    },
+                    Cw20Coin {
+                        address: MOCK_CONTRACT_ADDR.to_string(),
+                        amount: Uint128::from(1000u128),
+                    },
+                ],
+                ..Default::default()
+            },
+            conviction: Some(ConvictionConfig {
+                decay: Decimal::percent(50),
+                max_ratio: Decimal::percent(50),
+                weight: Decimal::percent(1),
+            }),
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::ProposeGrant(GrantProposeMsg {
+                title: "🦄".to_string(),
+                body: "fund a unicorn sanctuary".to_string(),
+                recipient: "sanctuary".to_string(),
+                amount: Uint128::from(100u128),
+            }),
+        )
+        .unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::StakeConviction {
+                proposal_id: 0,
+                amount: Uint128::from(200u128),
+            },
+        )
+        .unwrap();
+
+        // The grant asks for a tenth of the treasury. It needs
+        // 0.01 * 2000 / (0.5 * 0.4^2) = 250 conviction and a stake of
+        // 200 builds 200, 300, 350, ... conviction over the
+        // following blocks.
+        let mut env = mock_env();
+        env.block.height += 1;
+        let res = query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::Conviction { proposal_id: 0 },
+        )
+        .unwrap();
+        let conviction: ConvictionResponse = from_binary(&res).unwrap();
+        assert_eq!(
+            conviction,
+            ConvictionResponse {
+                conviction: Uint128::from(200u128),
+                threshold: Some(Uint128::from(250u128)),
+                staked: Uint128::from(200u128),
+            }
+        );
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            ExecuteMsg::UpdateConviction { proposal_id: 0 },
+        )
+        .unwrap();
+        assert_eq!(get_balance(deps.as_ref(), "sanctuary"), Uint128::zero());
+
+        env.block.height += 1;
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            ExecuteMsg::UpdateConviction { proposal_id: 0 },
+        )
+        .unwrap();
+        assert_eq!(
+            get_balance(deps.as_ref(), "sanctuary"),
+            Uint128::from(100u128)
+        );
+        assert_eq!(
+            get_balance(deps.as_ref(), MOCK_CONTRACT_ADDR),
+            Uint128::from(900u128)
+        );
+
+        // Stakes can be removed once the grant has passed.
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            ExecuteMsg::StakeConviction {
+                proposal_id: 0,
+                amount: Uint128::from(1u128),
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::VoteOnCompletedProposal);
+        execute(
+            deps.as_mut(),
+            env,
+            info,
+            ExecuteMsg::UnstakeConviction {
+                proposal_id: 0,
+                amount: Uint128::from(200u128),
+            },
+        )
+        .unwrap();
+        assert_eq!(get_balance(deps.as_ref(), "🦄"), Uint128::from(1000u128));
+    }
+
+    #[test]
+    fn conviction_config() {
+        let config = ConvictionConfig {
+            decay: Decimal::percent(50),
+            max_ratio: Decimal::percent(50),
+            weight: Decimal::zero(),
+        };
+        assert_eq!(
+            config.validate().unwrap_err(),
+            ContractError::InvalidConvictionConfig {
+                reason: "weight must be greater than zero".to_string()
+            }
+        );
+
+        // A threshold too large to represent can never be reached.
+        let config = ConvictionConfig {
+            weight: Decimal::from_ratio(u128::MAX, 1_000_000_000_000_000_000u128),
+            ..config
+        };
+        config.validate().unwrap();
+        assert_eq!(
+            config.threshold(Uint128::MAX, Uint128::from(100u128), Uint128::from(1u128)),
+            None
+        );
+    }
+
+    #[test]
+    fn conviction_unstake_while_paused() {
+        let mut deps = mock_dependencies(&[]);
+        let info = mock_info("🦄", &[]);
+
+        let msg = InstantiateMsg {
+            quorum: Uint128::from(10u128),
+            token_info: TokenInstantiateInfo {
+                name: "item-dao".to_string(),
+                symbol: "IDAO".to_string(),
+                decimals: 3,
+                initial_balances: vec![
+                    Cw20Coin {
+                        address: "🦄".to_string(),
+                        amount: Uint128::from(1000u128),
+                    },
+                    Cw20Coin {
+                        address: MOCK_CONTRACT_ADDR.to_string(),
+                        amount: Uint128::from(1000u128),
+                    },
+                ],
+                ..Default::default()
+            },
+            conviction: Some(ConvictionConfig {
+                decay: Decimal::percent(50),
+                max_ratio: Decimal::percent(50),
+                weight: Decimal::percent(1),
+            }),
+            guardian: Some("🐉".to_string()),
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::ProposeGrant(GrantProposeMsg {
+                title: "🦄".to_string(),
+                body: "fund a unicorn sanctuary".to_string(),
+                recipient: "sanctuary".to_string(),
+                amount: Uint128::from(100u128),
+            }),
+        )
+        .unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::StakeConviction {
+                proposal_id: 0,
+                amount: Uint128::from(200u128),
+            },
+        )
+        .unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("🐉", &[]),
+            ExecuteMsg::Pause { transfers: false },
+        )
+        .unwrap();
+
+        // The grant has built up enough conviction to pass but is not
+        // paid out while the DAO is paused. The stake is still
+        // returned.
+        let mut env = mock_env();
+        env.block.height += 2;
+        execute(
+            deps.as_mut(),
+            env,
+            info,
+            ExecuteMsg::UnstakeConviction {
+                proposal_id: 0,
+                amount: Uint128::from(100u128),
+            },
+        )
+        .unwrap();
+        assert_eq!(get_balance(deps.as_ref(), "sanctuary"), Uint128::zero());
+        assert_eq!(
+            get_balance(deps.as_ref(), MOCK_CONTRACT_ADDR),
+            Uint128::from(1000u128)
+        );
+        assert_eq!(get_balance(deps.as_ref(), "🦄"), Uint128::from(900u128));
+    }
+
+    #[test]
+    fn ranked_choice() {
+        let mut deps = mock_dependencies(&[]);
+        let voters = ["🦄", "🐴", "🦓"];
+
+        let msg = InstantiateMsg {
+            quorum: Uint128::from(100u128),
+            token_info: TokenInstantiateInfo {
+                name: "item-dao".to_string(),
+                symbol: "IDAO".to_string(),
+                decimals: 3,
+                initial_balances: voters
+                    .iter()
+                    .map(|v| Cw20Coin {
+                        address: v.to_string(),
+                        amount: Uint128::from(100u128),
+                    })
+                    .collect(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("🦄", &[]), msg).unwrap();
+
+        let name = |name: &str| DaoItem {
+            name: "language name".to_string(),
+            contents: name.to_string(),
+        };
+        let proposal = ProposeMsg {
+            title: "name our language".to_string(),
+            body: "it needs a name".to_string(),
+            action: ProposeAction::MultipleChoice {
+                choices: vec![
+                    Choice {
+                        title: "keep it nameless".to_string(),
+                        action: None,
+                    },
+                    Choice {
+                        title: "horsish".to_string(),
+                        action: Some(ProposeAction::AddItem(name("horsish"))),
+                    },
+                    Choice {
+                        title: "stripey".to_string(),
+                        action: Some(ProposeAction::AddItem(name("stripey"))),
+                    },
+                ],
+            },
+        };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("🦄", &[]),
+            ExecuteMsg::Propose(proposal),
+        )
+        .unwrap();
+
+        let ballot = |position: crate::msg::VotePosition, amount: u128, ranking: Vec<u32>| {
+            ExecuteMsg::Vote(VoteMsg {
+                proposal_id: 0,
+                position,
+                amount: Uint128::from(amount),
+                ranking,
+            })
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("🐴", &[]),
+            ballot(crate::msg::VotePosition::No, 10, vec![]),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::InvalidChoice { .. }));
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("🐴", &[]),
+            ballot(crate::msg::VotePosition::Yes, 10, vec![1, 3]),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::InvalidChoice { .. }));
+
+        for (voter, amount, ranking) in [("🦄", 40u128, vec![0]), ("🐴", 35, vec![1, 0])] {
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info(voter, &[]),
+                ballot(crate::msg::VotePosition::Yes, amount, ranking),
+            )
+            .unwrap();
+        }
+        let tally = |deps: Deps| -> ChoiceTallyResponse {
+            let res = query(deps, mock_env(), QueryMsg::ChoiceTally { proposal_id: 0 }).unwrap();
+            from_binary(&res).unwrap()
+        };
+        assert_eq!(
+            tally(deps.as_ref()),
+            ChoiceTallyResponse {
+                first_choices: vec![40u128.into(), 35u128.into(), 0u128.into()],
+                winner: Some(0),
+            }
+        );
+
+        // Stripey is eliminated first and its supporters' second
+        // choice carries horsish past the first choice leader.
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("🦓", &[]),
+            ballot(crate::msg::VotePosition::Yes, 25, vec![2, 1]),
+        )
+        .unwrap();
+        assert_eq!(tally(deps.as_ref()).winner, Some(1));
+
+        let proposals = load_proposals(&deps.storage);
+        assert_eq!(proposals[0].status, ProposalStatus::Passed);
+        let items = ITEMS.load(&deps.storage).unwrap();
+        assert_eq!(items, vec![name("horsish")]);
+    }
+
+    #[test]
+    fn approval_queue() {
+        let mut deps = mock_dependencies(&[]);
+
+        let msg = InstantiateMsg {
+            quorum: Uint128::from(10u128),
+            proposal_cost: Uint128::from(5u128),
+            token_info: TokenInstantiateInfo {
+                name: "item-dao".to_string(),
+                symbol: "IDAO".to_string(),
+                decimals: 3,
+                initial_balances: vec![
+                    Cw20Coin {
+                        address: "🦄".to_string(),
+                        amount: Uint128::from(100u128),
+                    },
+                    Cw20Coin {
+                        address: "🐴".to_string(),
+                        amount: Uint128::from(100u128),
+                    },
+                ],
+                ..Default::default()
+            },
+            pre_propose: Some(PreProposeInfo {
+                approvers: vec!["approver".to_string()],
+                sponsor_threshold: Some(Uint128::from(20u128)),
+            }),
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("🦄", &[]), msg).unwrap();
+
+        for _ in 0..2 {
+            let proposal = ProposeMsg {
+                title: "🦄".to_string(),
+                body: "🦄".to_string(),
+                action: ProposeAction::RemoveItem { id: 0 },
+            };
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("🦄", &[]),
+                ExecuteMsg::Propose(proposal),
+            )
+            .unwrap();
+        }
+        assert_eq!(get_balance(deps.as_ref(), "🦄"), Uint128::from(90u128));
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::ApprovalQueue).unwrap();
+        let queue: ApprovalQueueResponse = from_binary(&res).unwrap();
+        assert_eq!(
+            queue.proposals.iter().map(|p| p.id).collect::<Vec<_>>(),
+            vec![0, 1]
+        );
+
+        // Only the proposer may withdraw and they get their deposit
+        // back.
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("🐴", &[]),
+            ExecuteMsg::WithdrawProposal { proposal_id: 1 },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized);
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("🦄", &[]),
+            ExecuteMsg::WithdrawProposal { proposal_id: 1 },
+        )
+        .unwrap();
+        assert_eq!(get_balance(deps.as_ref(), "🦄"), Uint128::from(95u128));
+        assert_eq!(
+            res.events,
+            vec![Event::new("proposal_completed")
+                .add_attribute("proposal_id", "1")
+                .add_attribute("status", "withdrawn")
+                .add_attribute("yes", "0")
+                .add_attribute("no", "0")
+                .add_attribute("abstain", "0")
+                .add_attribute("no_with_veto", "0")]
+        );
+
+        let err = vote(deps.as_mut(), "🦄", 0, crate::msg::VotePosition::Yes, 10).unwrap_err();
+        assert_eq!(err, ContractError::ProposalNotApproved);
+
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("🐴", &[]),
+            ExecuteMsg::Approve { proposal_id: 0 },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized);
+
+        // Sponsors are refunded once the proposal leaves the queue.
+        let mut emitted = vec![];
+        for _ in 0..2 {
+            let res = execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("🐴", &[]),
+                ExecuteMsg::Sponsor {
+                    proposal_id: 0,
+                    amount: Uint128::from(10u128),
+                },
+            )
+            .unwrap();
+            emitted.extend(res.events);
+        }
+        assert_eq!(get_balance(deps.as_ref(), "🐴"), Uint128::from(100u128));
+        assert_eq!(
+            emitted,
+            vec![Event::new("proposal_status_changed")
+                .add_attribute("proposal_id", "0")
+                .add_attribute("status", "pending")]
+        );
+
+        let proposal = ProposeMsg {
+            title: "🦄".to_string(),
+            body: "🦄".to_string(),
+            action: ProposeAction::RemoveItem { id: 0 },
+        };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("🦄", &[]),
+            ExecuteMsg::Propose(proposal),
+        )
+        .unwrap();
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("approver", &[]),
+            ExecuteMsg::Approve { proposal_id: 2 },
+        )
+        .unwrap();
+        assert_eq!(
+            res.events,
+            vec![Event::new("proposal_status_changed")
+                .add_attribute("proposal_id", "2")
+                .add_attribute("status", "pending")]
+        );
+
+        let proposals = load_proposals(&deps.storage);
+        assert_eq!(proposals[0].status, ProposalStatus::Pending);
+        assert_eq!(proposals[1].status, ProposalStatus::Withdrawn);
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::ApprovalQueue).unwrap();
+        let queue: ApprovalQueueResponse = from_binary(&res).unwrap();
+        assert!(queue.proposals.is_empty());
+    }
+
+    #[test]
+    fn paused_approval_queue() {
+        let mut deps = mock_dependencies(&[]);
+
+        let msg = InstantiateMsg {
+            quorum: Uint128::from(10u128),
+            proposal_cost: Uint128::from(5u128),
+            token_info: TokenInstantiateInfo {
+                name: "item-dao".to_string(),
+                symbol: "IDAO".to_string(),
+                decimals: 3,
+                initial_balances: vec![Cw20Coin {
+                    address: "🦄".to_string(),
+                    amount: Uint128::from(100u128),
+                }],
+                ..Default::default()
+            },
+            pre_propose: Some(PreProposeInfo {
+                approvers: vec!["approver".to_string()],
+                sponsor_threshold: Some(Uint128::from(20u128)),
+            }),
+            guardian: Some("🐉".to_string()),
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("🦄", &[]), msg).unwrap();
+
+        for _ in 0..3 {
+            propose(deps.as_mut(), "🦄", ProposeAction::RemoveItem { id: 0 }).unwrap();
+        }
+        let approve = |proposal_id| ExecuteMsg::Approve { proposal_id };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("approver", &[]),
+            approve(0),
+        )
+        .unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("🐉", &[]),
+            ExecuteMsg::Pause { transfers: false },
+        )
+        .unwrap();
+
+        // Nothing may move through the queue while paused.
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("approver", &[]),
+            approve(1),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Paused);
+        let sponsor = ExecuteMsg::Sponsor {
+            proposal_id: 1,
+            amount: Uint128::from(20u128),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("🦄", &[]), sponsor).unwrap_err();
+        assert_eq!(err, ContractError::Paused);
+
+        // Nor may the proposer cancel an approved proposal.
+        let cancel = ExecuteMsg::Cancel { proposal_id: 0 };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("🦄", &[]), cancel).unwrap_err();
+        assert_eq!(err, ContractError::Paused);
+
+        // Proposals awaiting approval may still be withdrawn.
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("🦄", &[]),
+            ExecuteMsg::WithdrawProposal { proposal_id: 2 },
+        )
+        .unwrap();
+        let proposals = load_proposals(&deps.storage);
+        assert_eq!(proposals[0].status, ProposalStatus::Pending);
+        assert_eq!(proposals[1].status, ProposalStatus::PendingApproval);
+        assert_eq!(proposals[2].status, ProposalStatus::Withdrawn);
+        assert_eq!(get_balance(deps.as_ref(), "🦄"), Uint128::from(90u128));
+    }
+
+    #[test]
+    fn cancel_proposal() {
+        let mut deps = mock_dependencies(&[]);
+
+        let msg = InstantiateMsg {
+            quorum: Uint128::from(100u128),
+            proposal_cost: Uint128::from(10u128),
+            token_info: TokenInstantiateInfo {
+                name: "item-dao".to_string(),
+                symbol: "IDAO".to_string(),
+                decimals: 3,
+                initial_balances: vec![
+                    Cw20Coin {
+                        address: "🦄".to_string(),
+                        amount: Uint128::from(100u128),
+                    },
+                    Cw20Coin {
+                        address: "🐴".to_string(),
+                        amount: Uint128::from(100u128),
+                    },
+                ],
+                ..Default::default()
+            },
+            cancel_policy: CancelPolicy {
+                after_votes: false,
+                fee: Decimal::percent(20),
+            },
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("🦄", &[]), msg).unwrap();
+
+        let proposal = ProposeMsg {
+            title: "🦄".to_string(),
+            body: "add a unicorn with a typo".to_string(),
+            action: ProposeAction::AddItem(DaoItem {
+                name: "unicron".to_string(),
+                contents: "🦄".to_string(),
+            }),
+        };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("🦄", &[]),
+            ExecuteMsg::Propose(proposal),
+        )
+        .unwrap();
+        vote(deps.as_mut(), "🐴", 0, crate::msg::VotePosition::No, 5).unwrap();
+
+        let cancel = ExecuteMsg::Cancel { proposal_id: 0 };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("🐴", &[]),
+            cancel.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized);
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("🦄", &[]),
+            cancel.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::CancelNotAllowed);
+
+        // Once the vote is withdrawn the proposal may be cancelled and
+        // the treasury keeps the fee.
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("🐴", &[]),
+            ExecuteMsg::Withdraw(WithdrawVoteMsg { proposal_id: 0 }),
+        )
+        .unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("🦄", &[]),
+            cancel.clone(),
+        )
+        .unwrap();
+        assert_eq!(get_balance(deps.as_ref(), "🦄"), Uint128::from(98u128));
+        assert_eq!(get_balance(deps.as_ref(), "🐴"), Uint128::from(100u128));
+        assert_eq!(
+            get_balance(deps.as_ref(), MOCK_CONTRACT_ADDR),
+            Uint128::from(2u128)
+        );
+
+        let proposals = load_proposals(&deps.storage);
+        assert_eq!(proposals[0].status, ProposalStatus::Cancelled);
+        let err = execute(deps.as_mut(), mock_env(), mock_info("🦄", &[]), cancel).unwrap_err();
+        assert_eq!(err, ContractError::ProposalNotPending);
+    }
+
+    #[test]
+    fn timelock() {
+        let mut deps = mock_dependencies(&[]);
+
+        let msg = InstantiateMsg {
+            quorum: Uint128::from(100u128),
+            proposal_cost: Uint128::from(10u128),
+            token_info: TokenInstantiateInfo {
+                name: "item-dao".to_string(),
+                symbol: "IDAO".to_string(),
+                decimals: 3,
+                initial_balances: vec![Cw20Coin {
+                    address: "🦄".to_string(),
+                    amount: Uint128::from(200u128),
+                }],
+                ..Default::default()
+            },
+            timelock: Some(Duration::Height(10)),
+            guardian: Some("🐉".to_string()),
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("🦄", &[]), msg).unwrap();
+
+        for (id, name) in ["unicorn", "dragon"].iter().enumerate() {
+            let item = DaoItem {
+                name: name.to_string(),
+                contents: "🦄".to_string(),
+            };
+            propose(deps.as_mut(), "🦄", ProposeAction::AddItem(item)).unwrap();
+            vote(
+                deps.as_mut(),
+                "🦄",
+                id as u64,
+                crate::msg::VotePosition::Yes,
+                100,
+            )
+            .unwrap();
+            claim_refund(deps.as_mut(), "🦄", id as u64);
+        }
+
+        // Both proposals passed but neither has taken effect. Deposits
+        // are returned straight away and votes may be claimed.
+        let executable_at = Duration::Height(10).after(&mock_env().block);
+        let proposals = load_proposals(&deps.storage);
+        assert_eq!(
+            proposals[0].status,
+            ProposalStatus::Queued { executable_at }
+        );
+        assert_eq!(ITEMS.load(&deps.storage).unwrap().len(), 0);
+        assert_eq!(get_balance(deps.as_ref(), "🦄"), Uint128::from(200u128));
+
+        let execute_msg = ExecuteMsg::ExecuteProposal { proposal_id: 0 };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("🐴", &[]),
+            execute_msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::TimelockNotElapsed {
+                executable_at: executable_at.to_string()
+            }
+        );
+
+        // Only the guardian may cancel a queued proposal.
+        let cancel = ExecuteMsg::Cancel { proposal_id: 1 };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("🦄", &[]),
+            cancel.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized);
+        execute(deps.as_mut(), mock_env(), mock_info("🐉", &[]), cancel).unwrap();
+
+        let mut env = mock_env();
+        env.block.height += 10;
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("🐴", &[]),
+            execute_msg.clone(),
+        )
+        .unwrap();
+        assert_eq!(
+            res.events,
+            vec![
+                Event::new("proposal_status_changed")
+                    .add_attribute("proposal_id", "0")
+                    .add_attribute("status", "passed"),
+                Event::new("item_added")
+                    .add_attribute("item_id", "0")
+                    .add_attribute("name", "unicorn"),
+            ]
+        );
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("🐴", &[]),
+            ExecuteMsg::ExecuteProposal { proposal_id: 1 },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::ProposalNotQueued);
+
+        let items = ITEMS.load(&deps.storage).unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].name, "unicorn");
+        let proposals = load_proposals(&deps.storage);
+        assert_eq!(proposals[0].status, ProposalStatus::Passed);
+        assert_eq!(proposals[1].status, ProposalStatus::Cancelled);
+        let err = execute(deps.as_mut(), env, mock_info("🐴", &[]), execute_msg).unwrap_err();
+        assert_eq!(err, ContractError::ProposalNotQueued);
+    }
+
+    #[test]
+    fn rage_quit() {
+        let mut deps = mock_dependencies(&coins(1000, "ujuno"));
+
+        let msg = InstantiateMsg {
+            quorum: Uint128::from(100u128),
+            proposal_cost: Uint128::from(10u128),
+            token_info: TokenInstantiateInfo {
+                name: "item-dao".to_string(),
+                symbol: "IDAO".to_string(),
+                decimals: 3,
+                initial_balances: vec![
+                    Cw20Coin {
+                        address: "🦄".to_string(),
+                        amount: Uint128::from(150u128),
+                    },
+                    Cw20Coin {
+                        address: "🐴".to_string(),
+                        amount: Uint128::from(50u128),
+                    },
+                    Cw20Coin {
+                        address: MOCK_CONTRACT_ADDR.to_string(),
+                        amount: Uint128::from(100u128),
+                    },
+                ],
+                ..Default::default()
+            },
+            timelock: Some(Duration::Height(10)),
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("🦄", &[]), msg).unwrap();
+
+        let proposal = ProposeMsg {
+            title: "🦄".to_string(),
+            body: "spend the treasury".to_string(),
+            action: ProposeAction::BankSend {
+                to: "🦄".to_string(),
+                amount: coins(500, "ujuno"),
+            },
+        };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("🦄", &[]),
+            ExecuteMsg::Propose(proposal),
+        )
+        .unwrap();
+
+        // Nobody has voted against a queued proposal yet.
+        let rage_quit = ExecuteMsg::RageQuit {
+            amount: Uint128::from(20u128),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("🐴", &[]),
+            rage_quit.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::NotDissenter);
+
+        for (voter, position, amount) in [
+            ("🐴", crate::msg::VotePosition::No, 50u128),
+            ("🦄", crate::msg::VotePosition::Yes, 100u128),
+        ]
+        .iter()
+        {
+            vote(deps.as_mut(), voter, 0, position.clone(), *amount).unwrap();
+        }
+
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("🦄", &[]),
+            rage_quit.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::NotDissenter);
+
+        // 🐴 burns 20 of the 200 tokens held outside the treasury
+        // and so gets a tenth of its native coins.
+        claim_refund(deps.as_mut(), "🐴", 0);
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("🐴", &[]),
+            rage_quit.clone(),
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::reply_on_success(
+                BankMsg::Send {
+                    to_address: "🐴".to_string(),
+                    amount: coins(100, "ujuno"),
+                },
+                crate::ragequit::RAGE_QUIT_REPLY_ID
+            )]
+        );
+        assert_eq!(get_balance(deps.as_ref(), "🐴"), Uint128::from(30u128));
+        let info = tokens::query_token_info(deps.as_ref()).unwrap();
+        assert_eq!(info.total_supply, Uint128::from(280u128));
+
+        // No further rage quits until the payout has been sent.
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("🐴", &[]),
+            rage_quit.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::RageQuitInProgress);
+        let reply_msg = Reply {
+            id: crate::ragequit::RAGE_QUIT_REPLY_ID,
+            result: cosmwasm_std::ContractResult::Ok(cosmwasm_std::SubMsgExecutionResponse {
+                events: vec![],
+                data: None,
+            }),
+        };
+        reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+        execute(deps.as_mut(), mock_env(), mock_info("🐴", &[]), rage_quit).unwrap();
+    }
+
+    #[test]
+    fn guardian_pause() {
+        let mut deps = mock_dependencies(&[]);
+
+        let msg = InstantiateMsg {
+            quorum: Uint128::from(100u128),
+            proposal_cost: Uint128::from(10u128),
+            token_info: TokenInstantiateInfo {
+                name: "item-dao".to_string(),
+                symbol: "IDAO".to_string(),
+                decimals: 3,
+                initial_balances: vec![Cw20Coin {
+                    address: "🦄".to_string(),
+                    amount: Uint128::from(400u128),
+                }],
+                ..Default::default()
+            },
+            guardian: Some("🐉".to_string()),
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("🦄", &[]), msg).unwrap();
+
+        let pause = ExecuteMsg::Pause { transfers: true };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("🦄", &[]),
+            pause.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized);
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("🐉", &[]),
+            pause.clone(),
+        )
+        .unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::PauseInfo).unwrap();
+        let info: PauseInfoResponse = from_binary(&res).unwrap();
+        assert_eq!(
+            info,
+            PauseInfoResponse {
+                paused: true,
+                transfers_paused: true,
+                guardian: Some(Addr::unchecked("🐉")),
+            }
+        );
+
+        let add_item = ProposeAction::AddItem(DaoItem {
+            name: "unicorn".to_string(),
+            contents: "🦄".to_string(),
+        });
+        let err = propose(deps.as_mut(), "🦄", add_item.clone()).unwrap_err();
+        assert_eq!(err, ContractError::Paused);
+        let transfer = ExecuteMsg::Transfer {
+            recipient: "🐴".to_string(),
+            amount: Uint128::from(1u128),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("🦄", &[]), transfer).unwrap_err();
+        assert_eq!(err, ContractError::Paused);
+
+        // Governance may still lift the pause and remove the guardian.
+        let actions = vec![
+            ProposeAction::ChangeGuardian { guardian: None },
+            ProposeAction::Unpause,
+        ];
+        for (id, action) in actions.into_iter().enumerate() {
+            propose(deps.as_mut(), "🦄", action).unwrap();
+            vote(
+                deps.as_mut(),
+                "🦄",
+                id as u64,
+                crate::msg::VotePosition::Yes,
+                100,
+            )
+            .unwrap();
+        }
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::PauseInfo).unwrap();
+        let info: PauseInfoResponse = from_binary(&res).unwrap();
+        assert_eq!(
+            info,
+            PauseInfoResponse {
+                paused: false,
+                transfers_paused: false,
+                guardian: None,
+            }
+        );
+        propose(deps.as_mut(), "🦄", add_item).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), mock_info("🐉", &[]), pause).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized);
+    }
+
+    #[test]
+    fn events() {
+        let mut deps = mock_dependencies(&[]);
+
+        let msg = InstantiateMsg {
+            quorum: Uint128::from(100u128),
+            proposal_cost: Uint128::from(10u128),
+            token_info: TokenInstantiateInfo {
+                name: "item-dao".to_string(),
+                symbol: "IDAO".to_string(),
+                decimals: 3,
+                initial_balances: vec![Cw20Coin {
+                    address: "🦄".to_string(),
+                    amount: Uint128::from(400u128),
+                }],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("🦄", &[]), msg).unwrap();
+
+        let add_item = ProposeAction::AddItem(DaoItem {
+            name: "unicorn".to_string(),
+            contents: "🦄".to_string(),
+        });
+        let res = propose(deps.as_mut(), "🦄", add_item).unwrap();
+        assert_eq!(
+            res.events,
+            vec![Event::new("proposal_created")
+                .add_attribute("proposal_id", "0")
+                .add_attribute("proposer", "🦄")
+                .add_attribute("title", "🦄")
+                .add_attribute("action", "add_item")
+                .add_attribute("status", "pending")]
+        );
+
+        let vote_weighted = ExecuteMsg::VoteWeighted {
+            proposal_id: 0,
+            amount: Uint128::from(100u128),
+            options: vec![
+                (crate::msg::VotePosition::Yes, Decimal::percent(60)),
+                (crate::msg::VotePosition::Abstain, Decimal::percent(40)),
+            ],
+        };
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("🦄", &[]),
+            vote_weighted,
+        )
+        .unwrap();
+        let vote_cast = |position, amount| {
+            Event::new("vote_cast")
+                .add_attribute("proposal_id", "0")
+                .add_attribute("voter", "🦄")
+                .add_attribute("position", position)
+                .add_attribute("amount", amount)
+                .add_attribute("status", "passed")
+        };
+        assert_eq!(
+            res.events,
+            vec![
+                vote_cast("yes", "60"),
+                vote_cast("abstain", "40"),
+                Event::new("proposal_completed")
+                    .add_attribute("proposal_id", "0")
+                    .add_attribute("status", "passed")
+                    .add_attribute("yes", "60")
+                    .add_attribute("no", "0")
+                    .add_attribute("abstain", "40")
+                    .add_attribute("no_with_veto", "0"),
+                Event::new("item_added")
+                    .add_attribute("item_id", "0")
+                    .add_attribute("name", "unicorn"),
+            ]
+        );
+
+        let actions = vec![
+            ProposeAction::RemoveItem { id: 0 },
+            ProposeAction::ChangeQuorum {
+                new_quorum: Uint128::from(150u128),
+            },
+        ];
+        let mut emitted = vec![];
+        for (id, action) in actions.into_iter().enumerate() {
+            propose(deps.as_mut(), "🦄", action).unwrap();
+            let res = vote(
+                deps.as_mut(),
+                "🦄",
+                id as u64 + 1,
+                crate::msg::VotePosition::Yes,
+                100,
+            )
+            .unwrap();
+            emitted.push(res.events.last().unwrap().clone());
+        }
+        assert_eq!(
+            emitted,
+            vec![
+                Event::new("item_removed").add_attribute("item_id", "0"),
+                Event::new("config_changed")
+                    .add_attribute("key", "quorum")
+                    .add_attribute("value", "\"150\""),
+            ]
+        );
+    }
+
+    #[test]
+    fn update_config() {
+        let mut deps = mock_dependencies(&[]);
+
+        let msg = InstantiateMsg {
+            quorum: Uint128::from(100u128),
+            proposal_cost: Uint128::from(10u128),
+            token_info: TokenInstantiateInfo {
+                name: "item-dao".to_string(),
+                symbol: "IDAO".to_string(),
+                decimals: 3,
+                initial_balances: vec![Cw20Coin {
+                    address: "🦄".to_string(),
+                    amount: Uint128::from(400u128),
+                }],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("🦄", &[]), msg).unwrap();
+
+        let err = propose(
+            deps.as_mut(),
+            "🦄",
+            ProposeAction::UpdateConfig(ConfigUpdate::default()),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::EmptyConfigUpdate);
+
+        // The update is checked as a whole.
+        let update = ConfigUpdate {
+            proposal_cost: Some(Uint128::from(20u128)),
+            quorum: Some(Uint128::from(500u128)),
+            ..Default::default()
+        };
+        let err = propose(deps.as_mut(), "🦄", ProposeAction::UpdateConfig(update)).unwrap_err();
+        assert_eq!(err, ContractError::InvalidQuorum);
+
+        let update = ConfigUpdate {
+            proposal_cost: Some(Uint128::from(20u128)),
+            quorum: Some(Uint128::from(150u128)),
+            veto_threshold: Some(Decimal::percent(30)),
+            ..Default::default()
+        };
+        propose(deps.as_mut(), "🦄", ProposeAction::UpdateConfig(update)).unwrap();
+        vote(deps.as_mut(), "🦄", 0, crate::msg::VotePosition::Yes, 100).unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Config).unwrap();
+        let config: ConfigResponse = from_binary(&res).unwrap();
+        assert_eq!(config.quorum, Uint128::from(150u128));
+        assert_eq!(config.proposal_cost, Uint128::from(20u128));
+        assert_eq!(config.veto_threshold, Some(Decimal::percent(30)));
+        assert_eq!(config.timelock, None);
+        assert_eq!(config.token_mode, TokenMode::Transferable);
+        assert_eq!(config.version.contract, CONTRACT_NAME);
+        assert_eq!(config.version.version, CONTRACT_VERSION);
+
+        // Optional settings can be cleared but not set and cleared at
+        // once.
+        let update = ConfigUpdate {
+            veto_threshold: Some(Decimal::percent(40)),
+            clear_veto_threshold: true,
+            ..Default::default()
+        };
+        let err = propose(deps.as_mut(), "🦄", ProposeAction::UpdateConfig(update)).unwrap_err();
+        assert_eq!(err, ContractError::ConflictingConfigUpdate);
+
+        let update = ConfigUpdate {
+            clear_veto_threshold: true,
+            ..Default::default()
+        };
+        propose(deps.as_mut(), "🦄", ProposeAction::UpdateConfig(update)).unwrap();
+        vote(deps.as_mut(), "🦄", 1, crate::msg::VotePosition::Yes, 150).unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Config).unwrap();
+        let config: ConfigResponse = from_binary(&res).unwrap();
+        assert_eq!(config.veto_threshold, None);
+        assert_eq!(config.quorum, Uint128::from(150u128));
+
+        // The update is checked again when it executes as the supply
+        // may have shrunk since it was proposed.
+        claim_refund(deps.as_mut(), "🦄", 0);
+        claim_refund(deps.as_mut(), "🦄", 1);
+        let update = ConfigUpdate {
+            quorum: Some(Uint128::from(350u128)),
+            ..Default::default()
+        };
+        propose(deps.as_mut(), "🦄", ProposeAction::UpdateConfig(update)).unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("🦄", &[]),
+            ExecuteMsg::Burn {
+                amount: Uint128::from(60u128),
+            },
+        )
+        .unwrap();
+        let err = vote(deps.as_mut(), "🦄", 2, crate::msg::VotePosition::Yes, 150).unwrap_err();
+        assert_eq!(err, ContractError::InvalidQuorum);
+    }
+
+    #[test]
+    fn schema_in_sync() {
+        use std::collections::BTreeSet;
+        use std::fs;
+        use std::path::Path;
+
+        // Unique to this run so that concurrent test runs don't
+        // write over each other.
+        let out_dir = std::env::temp_dir().join(format!("dao-schema-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&out_dir);
+        fs::create_dir_all(&out_dir).unwrap();
+        crate::schema::export_schemas(&out_dir);
+
+        let list = |dir: &Path| -> BTreeSet<String> {
+            fs::read_dir(dir)
+                .unwrap()
+                .map(|e| e.unwrap().file_name().into_string().unwrap())
+                .collect()
+        };
+        let committed = Path::new(env!("CARGO_MANIFEST_DIR")).join("schema");
+        let generated = list(&out_dir);
+        assert_eq!(
+            generated,
+            list(&committed),
+            "schema/ is out of date, run `cargo schema`"
+        );
+        for name in generated {
+            assert_eq!(
+                fs::read_to_string(out_dir.join(&name)).unwrap(),
+                fs::read_to_string(committed.join(&name)).unwrap(),
+                "schema/{} is out of date, run `cargo schema`",
+                name
+            );
+        }
+        fs::remove_dir_all(&out_dir).unwrap();
+    }
+
+    #[test]
+    fn proposal_ids_and_stamps() {
+        let mut deps = mock_dependencies(&[]);
+
+        let msg = InstantiateMsg {
+            quorum: Uint128::from(100u128),
+            proposal_cost: Uint128::from(10u128),
+            token_info: TokenInstantiateInfo {
+                name: "item-dao".to_string(),
+                symbol: "IDAO".to_string(),
+                decimals: 3,
+                initial_balances: vec![Cw20Coin {
+                    address: "🦄".to_string(),
+                    amount: Uint128::from(400u128),
+                }],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("🦄", &[]), msg).unwrap();
+
+        for name in ["unicorn", "dragon"].iter() {
+            let proposal = ProposeMsg {
+                title: name.to_string(),
+                body: "add an item".to_string(),
+                action: ProposeAction::AddItem(DaoItem {
+                    name: name.to_string(),
+                    contents: "🦄".to_string(),
+                }),
+            };
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("🦄", &[]),
+                ExecuteMsg::Propose(proposal),
+            )
+            .unwrap();
+        }
+        // The emitted ID matches the ID that the proposal is stored
+        // under.
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("🦄", &[]),
+            ExecuteMsg::Propose(ProposeMsg {
+                title: "🐴".to_string(),
+                body: "🐴".to_string(),
+                action: ProposeAction::RemoveItem { id: 0 },
+            }),
+        )
+        .unwrap();
+        assert!(res
+            .attributes
+            .contains(&cosmwasm_std::attr("proposal_id", "2")));
+
+        let mut later = mock_env();
+        later.block.height += 5;
+        later.block.time = later.block.time.plus_seconds(30);
+        let vote = VoteMsg {
+            proposal_id: 1,
+            position: crate::msg::VotePosition::Yes,
+            amount: Uint128::from(100u128),
+            ranking: vec![],
+        };
+        execute(
+            deps.as_mut(),
+            later.clone(),
+            mock_info("🦄", &[]),
+            ExecuteMsg::Vote(vote),
+        )
+        .unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::ListProposals).unwrap();
+        let proposals = from_binary::<ProposalListResponse>(&res).unwrap().proposals;
+        assert_eq!(
+            proposals.iter().map(|p| p.id).collect::<Vec<_>>(),
+            vec![0, 1, 2]
+        );
+        let created = BlockStamp::from(&mock_env().block);
+        assert!(proposals.iter().all(|p| p.created == created));
+        assert_eq!(proposals[0].completed, None);
+        assert_eq!(proposals[1].title, "dragon");
+        assert_eq!(proposals[1].status, ProposalStatus::Passed);
+        assert_eq!(proposals[1].completed, Some(BlockStamp::from(&later.block)));
+    }
+
+    #[test]
+    fn list_proposals_by_index() {
+        let mut deps = mock_dependencies(&[]);
+
+        let msg = InstantiateMsg {
+            quorum: Uint128::from(100u128),
+            proposal_cost: Uint128::from(10u128),
+            token_info: TokenInstantiateInfo {
+                name: "item-dao".to_string(),
+                symbol: "IDAO".to_string(),
+                decimals: 3,
+                initial_balances: vec![
+                    Cw20Coin {
+                        address: "🦄".to_string(),
+                        amount: Uint128::from(400u128),
+                    },
+                    Cw20Coin {
+                        address: "🐴".to_string(),
+                        amount: Uint128::from(400u128),
+                    },
+                ],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("🦄", &[]), msg).unwrap();
+
+        // 🦄 proposes 0, 2, and 4. 🐴 proposes 1 and 3.
+        for id in 0..5u64 {
+            let proposer = if id % 2 == 0 { "🦄" } else { "🐴" };
+            let proposal = ProposeMsg {
+                title: id.to_string(),
+                body: "add an item".to_string(),
+                action: ProposeAction::AddItem(DaoItem {
+                    name: id.to_string(),
+                    contents: "🦄".to_string(),
+                }),
+            };
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info(proposer, &[]),
+                ExecuteMsg::Propose(proposal),
+            )
+            .unwrap();
+        }
+        vote(deps.as_mut(), "🐴", 2, crate::msg::VotePosition::Yes, 100).unwrap();
+
+        let list = |deps: Deps, msg: QueryMsg| -> Vec<u64> {
+            let res = query(deps, mock_env(), msg).unwrap();
+            from_binary::<ProposalListResponse>(&res)
+                .unwrap()
+                .proposals
+                .iter()
+                .map(|p| p.id)
+                .collect()
+        };
+
+        let by_status = |start_after, limit| QueryMsg::ListProposalsByStatus {
+            status: ProposalStatus::Pending,
+            start_after,
+            limit,
+        };
+        assert_eq!(list(deps.as_ref(), by_status(None, None)), vec![0, 1, 3, 4]);
+        assert_eq!(list(deps.as_ref(), by_status(None, Some(2))), vec![0, 1]);
+        assert_eq!(list(deps.as_ref(), by_status(Some(1), Some(2))), vec![3, 4]);
+        assert_eq!(
+            list(
+                deps.as_ref(),
+                QueryMsg::ListProposalsByStatus {
+                    status: ProposalStatus::Passed,
+                    start_after: None,
+                    limit: None,
+                }
+            ),
+            vec![2]
+        );
+
+        assert_eq!(
+            list(
+                deps.as_ref(),
+                QueryMsg::ListProposalsByProposer {
+                    proposer: "🦄".to_string(),
+                    start_after: Some(0),
+                    limit: None,
+                }
+            ),
+            vec![2, 4]
+        );
+        assert_eq!(
+            list(
+                deps.as_ref(),
+                QueryMsg::ListProposalsByProposer {
+                    proposer: "🐴".to_string(),
+                    start_after: None,
+                    limit: None,
+                }
+            ),
+            vec![1, 3]
+        );
+
+        assert_eq!(
+            list(
+                deps.as_ref(),
+                QueryMsg::ReverseProposals {
+                    start_before: None,
+                    limit: Some(3),
+                }
+            ),
+            vec![4, 3, 2]
+        );
+        assert_eq!(
+            list(
+                deps.as_ref(),
+                QueryMsg::ReverseProposals {
+                    start_before: Some(2),
+                    limit: None,
+                }
+            ),
+            vec![1, 0]
+        );
+    }
+
+    #[test]
+    fn list_votes_by_voter() {
+        use crate::msg::VotePosition;
+
+        let mut deps = mock_dependencies(&[]);
+
+        let msg = InstantiateMsg {
+            quorum: Uint128::from(100u128),
+            proposal_cost: Uint128::zero(),
+            token_info: TokenInstantiateInfo {
+                name: "item-dao".to_string(),
+                symbol: "IDAO".to_string(),
+                decimals: 3,
+                initial_balances: vec![Cw20Coin {
+                    address: "🦄".to_string(),
+                    amount: Uint128::from(400u128),
+                }],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("🦄", &[]), msg).unwrap();
+
+        for id in 0..3u64 {
+            let proposal = ProposeMsg {
+                title: id.to_string(),
+                body: "add an item".to_string(),
+                action: ProposeAction::AddItem(DaoItem {
+                    name: id.to_string(),
+                    contents: "🦄".to_string(),
+                }),
+            };
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("🦄", &[]),
+                ExecuteMsg::Propose(proposal),
+            )
+            .unwrap();
+        }
+
+        // Split a vote on 0, pass and claim 1, and vote on then
+        // withdraw from 2.
+        let weighted = ExecuteMsg::VoteWeighted {
+            proposal_id: 0,
+            amount: Uint128::from(50u128),
+            options: vec![
+                (VotePosition::Yes, Decimal::percent(60)),
+                (VotePosition::No, Decimal::percent(40)),
+            ],
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("🦄", &[]), weighted).unwrap();
+        for (proposal_id, amount) in [(1, 100u128), (2, 10u128)].iter() {
+            vote(
+                deps.as_mut(),
+                "🦄",
+                *proposal_id,
+                VotePosition::Yes,
+                *amount,
+            )
+            .unwrap();
+        }
+        claim_refund(deps.as_mut(), "🦄", 1);
+        let withdraw = ExecuteMsg::Withdraw(WithdrawVoteMsg { proposal_id: 2 });
+        execute(deps.as_mut(), mock_env(), mock_info("🦄", &[]), withdraw).unwrap();
+
+        let list = |start_after, limit| -> Vec<VoterVoteResponse> {
+            let msg = QueryMsg::ListVotesByVoter {
+                voter: "🦄".to_string(),
+                start_after,
+                limit,
+            };
+            let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+            from_binary::<VoterVoteListResponse>(&res).unwrap().votes
+        };
+        let vote = |proposal_id, position, amount: u128, locked| VoterVoteResponse {
+            proposal_id,
+            position,
+            amount: Uint128::from(amount),
+            locked,
+        };
+
+        assert_eq!(
+            list(None, None),
+            vec![
+                vote(0, VotePosition::Yes, 30, true),
+                vote(0, VotePosition::No, 20, true),
+                vote(1, VotePosition::Yes, 100, false),
+            ]
+        );
+        assert_eq!(
+            list(Some(0), Some(1)),
+            vec![vote(1, VotePosition::Yes, 100, false)]
+        );
+        assert_eq!(list(Some(1), None), vec![]);
+    }
+
+    #[test]
+    fn withdraw_all() {
+        let mut deps = mock_dependencies(&[]);
+
+        let msg = InstantiateMsg {
+            quorum: Uint128::from(100u128),
+            proposal_cost: Uint128::zero(),
+            token_info: TokenInstantiateInfo {
+                name: "item-dao".to_string(),
+                symbol: "IDAO".to_string(),
+                decimals: 3,
+                initial_balances: vec![Cw20Coin {
+                    address: "🦄".to_string(),
+                    amount: Uint128::from(400u128),
+                }],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("🦄", &[]), msg).unwrap();
+
+        // Vote on 0, 1, and 2 and pass 3.
+        for (proposal_id, amount) in [(0, 10u128), (1, 10), (2, 10), (3, 100)].iter() {
+            let proposal = ProposeMsg {
+                title: proposal_id.to_string(),
+                body: "add an item".to_string(),
+                action: ProposeAction::AddItem(DaoItem {
+                    name: proposal_id.to_string(),
+                    contents: "🦄".to_string(),
+                }),
+            };
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("🦄", &[]),
+                ExecuteMsg::Propose(proposal),
+            )
+            .unwrap();
+            vote(
+                deps.as_mut(),
+                "🦄",
+                *proposal_id,
+                crate::msg::VotePosition::Yes,
+                *amount,
+            )
+            .unwrap();
+        }
+        claim_refund(deps.as_mut(), "🦄", 3);
+        assert_eq!(get_balance(deps.as_ref(), "🦄"), Uint128::from(370u128));
+
+        let withdraw_all = |start_after, limit| ExecuteMsg::WithdrawAll { start_after, limit };
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("🦄", &[]),
+            withdraw_all(None, Some(2)),
+        )
+        .unwrap();
+        assert!(res
+            .attributes
+            .contains(&cosmwasm_std::attr("proposal_ids", "0,1")));
+        assert!(res
+            .attributes
+            .contains(&cosmwasm_std::attr("last_proposal_id", "1")));
+        assert!(res.attributes.contains(&cosmwasm_std::attr("tokens", "20")));
+        assert_eq!(get_balance(deps.as_ref(), "🦄"), Uint128::from(390u128));
+
+        // The completed proposal is skipped.
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("🦄", &[]),
+            withdraw_all(Some(1), None),
+        )
+        .unwrap();
+        assert!(res
+            .attributes
+            .contains(&cosmwasm_std::attr("proposal_ids", "2")));
+        assert_eq!(get_balance(deps.as_ref(), "🦄"), Uint128::from(400u128));
+        let prop: ProposalResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::GetProposal { proposal_id: 2 },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(prop.tally.yes, Uint128::zero());
+
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("🦄", &[]),
+            withdraw_all(None, None),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidZeroAmount);
+
+        // The vote on the passed proposal comes first but is not
+        // looked at again once it has been refunded.
+        let proposal = ProposeMsg {
+            title: "4".to_string(),
+            body: "add an item".to_string(),
+            action: ProposeAction::AddItem(DaoItem {
+                name: "4".to_string(),
+                contents: "🦄".to_string(),
+            }),
+        };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("🦄", &[]),
+            ExecuteMsg::Propose(proposal),
+        )
+        .unwrap();
+        vote(deps.as_mut(), "🦄", 4, crate::msg::VotePosition::Yes, 10).unwrap();
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("🦄", &[]),
+            withdraw_all(None, Some(1)),
+        )
+        .unwrap();
+        assert!(res
+            .attributes
+            .contains(&cosmwasm_std::attr("proposal_ids", "4")));
+        assert_eq!(get_balance(deps.as_ref(), "🦄"), Uint128::from(400u128));
+    }
+
+    #[test]
+    fn pull_refunds() {
+        let mut deps = mock_dependencies(&[]);
+
+        let msg = InstantiateMsg {
+            quorum: Uint128::from(100u128),
+            proposal_cost: Uint128::from(10u128),
+            token_info: TokenInstantiateInfo {
+                name: "item-dao".to_string(),
+                symbol: "IDAO".to_string(),
+                decimals: 3,
+                initial_balances: vec![
+                    Cw20Coin {
+                        address: "🦄".to_string(),
+                        amount: Uint128::from(200u128),
+                    },
+                    Cw20Coin {
+                        address: "🐴".to_string(),
+                        amount: Uint128::from(200u128),
+                    },
+                ],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("🦄", &[]), msg).unwrap();
+
+        let proposal = ProposeMsg {
+            title: "unicorn".to_string(),
+            body: "add an item".to_string(),
+            action: ProposeAction::AddItem(DaoItem {
+                name: "unicorn".to_string(),
+                contents: "🦄".to_string(),
+            }),
+        };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("🦄", &[]),
+            ExecuteMsg::Propose(proposal),
+        )
+        .unwrap();
+        for (voter, amount) in [("🐴", 40u128), ("🦄", 60u128)].iter() {
+            vote(
+                deps.as_mut(),
+                voter,
+                0,
+                crate::msg::VotePosition::Yes,
+                *amount,
+            )
+            .unwrap();
+        }
+
+        // The proposal passed and the deposit was refunded but the
+        // votes stay locked until they are claimed.
+        let proposals = load_proposals(&deps.storage);
+        assert_eq!(proposals[0].status, ProposalStatus::Passed);
+        assert_eq!(get_balance(deps.as_ref(), "🦄"), Uint128::from(140u128));
+        assert_eq!(get_balance(deps.as_ref(), "🐴"), Uint128::from(160u128));
+
+        let claimable = |deps: Deps, address: &str| {
+            let msg = QueryMsg::ClaimableRefunds {
+                address: address.to_string(),
+                start_after: None,
+                limit: None,
+            };
+            from_binary::<ClaimableRefundListResponse>(&query(deps, mock_env(), msg).unwrap())
+                .unwrap()
+                .refunds
+        };
+        assert_eq!(
+            claimable(deps.as_ref(), "🦄"),
+            vec![ClaimableRefundResponse {
+                proposal_id: 0,
+                amount: Uint128::from(60u128),
+            }]
+        );
+
+        let claim = ExecuteMsg::ClaimRefund { proposal_id: 0 };
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("🦄", &[]),
+            claim.clone(),
+        )
+        .unwrap();
+        assert_eq!(
+            res.events,
+            vec![Event::new("refund_claimed")
+                .add_attribute("proposal_id", "0")
+                .add_attribute("voter", "🦄")
+                .add_attribute("amount", "60")]
+        );
+        assert_eq!(get_balance(deps.as_ref(), "🦄"), Uint128::from(200u128));
+        assert_eq!(claimable(deps.as_ref(), "🦄"), vec![]);
+        let err = execute(deps.as_mut(), mock_env(), mock_info("🦄", &[]), claim).unwrap_err();
+        assert_eq!(err, ContractError::NoRefund);
+
+        // 🐴's stake is only refunded once they claim it.
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("🐴", &[]),
+            ExecuteMsg::Transfer {
+                recipient: "🦄".to_string(),
+                amount: Uint128::from(1u128),
+            },
+        )
+        .unwrap();
+        assert_eq!(get_balance(deps.as_ref(), "🐴"), Uint128::from(159u128));
+        assert_eq!(
+            claimable(deps.as_ref(), "🐴"),
+            vec![ClaimableRefundResponse {
+                proposal_id: 0,
+                amount: Uint128::from(40u128),
+            }]
+        );
+        claim_refund(deps.as_mut(), "🐴", 0);
+        assert_eq!(get_balance(deps.as_ref(), "🐴"), Uint128::from(199u128));
+        assert_eq!(claimable(deps.as_ref(), "🐴"), vec![]);
+    }
 }
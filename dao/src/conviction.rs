@@ -0,0 +1,389 @@
+use cosmwasm_std::{
+    Decimal, Deps, DepsMut, Env, MessageInfo, Response, StdError, StdResult, Storage, Uint128,
+    Uint256,
+};
+use cw_storage_plus::U64Key;
+use std::convert::TryInto;
+
+use crate::msg::{
+    ConvictionConfig, ConvictionResponse, GrantListResponse, GrantProposeMsg, GrantResponse,
+};
+use crate::pause;
+use crate::state::{
+    GrantProposal, ProposalStatus, BALANCES, CONVICTION_STAKED, GRANTS, STATE, TOKEN_INFO,
+};
+use crate::treasury;
+use crate::vesting;
+use crate::ContractError;
+
+impl ConvictionConfig {
+    pub fn validate(&self) -> Result<(), ContractError> {
+        if self.decay >= Decimal::one() {
+            return Err(ContractError::InvalidConvictionConfig {
+                reason: "decay must be less than one".to_string(),
+            });
+        }
+        if self.max_ratio.is_zero() || self.max_ratio > Decimal::one() {
+            return Err(ContractError::InvalidConvictionConfig {
+                reason: "max_ratio must be greater than zero and at most one".to_string(),
+            });
+        }
+        // Without a weight every grant would pass as soon as it is
+        // staked on.
+        if self.weight.is_zero() {
+            return Err(ContractError::InvalidConvictionConfig {
+                reason: "weight must be greater than zero".to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// The conviction of `grant` at block `height`.
+    ///
+    /// Each block conviction is multiplied by `decay` and the number
+    /// of tokens staked is added to it, so after `n` blocks of a
+    /// constant stake `x` it has grown to
+    /// `decay^n * conviction + x * (1 - decay^n) / (1 - decay)`.
+    pub fn conviction_at(&self, grant: &GrantProposal, height: u64) -> Uint128 {
+        let blocks = height.saturating_sub(grant.last_update);
+        let retained = pow(self.decay, blocks);
+        let added = grant.staked * (Decimal::one() - retained);
+        // Saturate rather than fail. Conviction this large passes any
+        // grant that is able to pass.
+        let added = div_decimal(added, Decimal::one() - self.decay).unwrap_or(Uint128::MAX);
+        (grant.conviction * retained).saturating_add(added)
+    }
+
+    /// The conviction a grant for `amount` tokens needs in order to
+    /// pass:
+    ///
+    /// `weight * supply / ((1 - decay) * (max_ratio - amount / treasury)^2)`
+    ///
+    /// Conviction tops out at `stake / (1 - decay)` so this asks for
+    /// a stake of `weight * supply` for a tiny grant, rising steeply
+    /// as the grant approaches `max_ratio` of the treasury. Returns
+    /// None if the grant asks for too much to ever pass.
+    pub fn threshold(
+        &self,
+        supply: Uint128,
+        treasury: Uint128,
+        amount: Uint128,
+    ) -> Option<Uint128> {
+        if treasury.is_zero() {
+            return None;
+        }
+        let ratio = Decimal::from_ratio(amount, treasury);
+        if ratio >= self.max_ratio {
+            return None;
+        }
+        let headroom = self.max_ratio - ratio;
+        let needed = mul_decimal(supply, self.weight)?;
+        let needed = div_decimal(needed, Decimal::one() - self.decay)?;
+        div_decimal(needed, headroom * headroom)
+    }
+}
+
+/// `base` to the power of `exp` by repeated squaring.
+fn pow(mut base: Decimal, mut exp: u64) -> Decimal {
+    let mut result = Decimal::one();
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = result * base;
+        }
+        base = base * base;
+        exp >>= 1;
+    }
+    result
+}
+
+/// `x * d`, or None if the result overflows.
+fn mul_decimal(x: Uint128, d: Decimal) -> Option<Uint128> {
+    (x.full_mul(d.atomics()) / Uint256::from(Decimal::one().atomics()))
+        .try_into()
+        .ok()
+}
+
+/// `x / d`, or None if `d` is zero or the result overflows.
+fn div_decimal(x: Uint128, d: Decimal) -> Option<Uint128> {
+    if d.is_zero() {
+        return None;
+    }
+    (x.full_mul(Decimal::one().atomics()) / Uint256::from(d.atomics()))
+        .try_into()
+        .ok()
+}
+
+fn load_config(storage: &dyn Storage) -> Result<ConvictionConfig, ContractError> {
+    STATE
+        .load(storage)?
+        .conviction
+        .ok_or(ContractError::ConvictionDisabled)
+}
+
+fn not_found(proposal_id: u64) -> StdError {
+    StdError::NotFound {
+        kind: format!("no such grant ID ({})", proposal_id),
+    }
+}
+
+pub fn execute_propose_grant(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: GrantProposeMsg,
+) -> Result<Response, ContractError> {
+    load_config(deps.storage)?;
+    pause::assert_not_paused(deps.storage, None)?;
+    if msg.amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount);
+    }
+    let recipient = deps.api.addr_validate(&msg.recipient)?;
+
+    let mut grants = GRANTS.may_load(deps.storage)?.unwrap_or_default();
+    let proposal_id = grants.len() as u64;
+    grants.push(GrantProposal {
+        title: msg.title,
+        body: msg.body,
+        proposer: info.sender,
+        recipient,
+        amount: msg.amount,
+        status: ProposalStatus::Pending,
+        staked: Uint128::zero(),
+        conviction: Uint128::zero(),
+        last_update: env.block.height,
+    });
+    GRANTS.save(deps.storage, &grants)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "propose_grant")
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_attribute("amount", msg.amount))
+}
+
+pub fn execute_stake(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    proposal_id: u64,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    if amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount);
+    }
+    pause::assert_not_paused(deps.storage, None)?;
+    let state = STATE.load(deps.storage)?;
+    if !state.locked_tokens_can_vote {
+        vesting::assert_unlocked(deps.storage, &env.block, &info.sender, amount)?;
+    }
+
+    let mut grants = GRANTS.may_load(deps.storage)?.unwrap_or_default();
+    let grant = grants
+        .get_mut(proposal_id as usize)
+        .ok_or_else(|| not_found(proposal_id))?;
+    if grant.status != ProposalStatus::Pending {
+        return Err(ContractError::VoteOnCompletedProposal);
+    }
+
+    BALANCES.update(
+        deps.storage,
+        &info.sender,
+        |balance: Option<Uint128>| -> StdResult<_> {
+            Ok(balance.unwrap_or_default().checked_sub(amount)?)
+        },
+    )?;
+    CONVICTION_STAKED.update(
+        deps.storage,
+        (&info.sender, U64Key::new(proposal_id)),
+        |staked: Option<Uint128>| -> StdResult<_> {
+            Ok(staked.unwrap_or_default().checked_add(amount)?)
+        },
+    )?;
+
+    // Conviction up until now was built by the old stake.
+    let config = load_config(deps.storage)?;
+    grant.conviction = config.conviction_at(grant, env.block.height);
+    grant.last_update = env.block.height;
+    grant.staked = grant.staked.checked_add(amount).map_err(StdError::from)?;
+    GRANTS.save(deps.storage, &grants)?;
+
+    Ok(try_pass(deps, env, &config, proposal_id)?
+        .add_attribute("method", "stake_conviction")
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_attribute("tokens", amount))
+}
+
+pub fn execute_unstake(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    proposal_id: u64,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    if amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount);
+    }
+    let mut grants = GRANTS.may_load(deps.storage)?.unwrap_or_default();
+    let grant = grants
+        .get_mut(proposal_id as usize)
+        .ok_or_else(|| not_found(proposal_id))?;
+
+    let key = (&info.sender, U64Key::new(proposal_id));
+    let remaining = CONVICTION_STAKED
+        .may_load(deps.storage, key.clone())?
+        .unwrap_or_default()
+        .checked_sub(amount)
+        .map_err(StdError::from)?;
+    if remaining.is_zero() {
+        CONVICTION_STAKED.remove(deps.storage, key);
+    } else {
+        CONVICTION_STAKED.save(deps.storage, key, &remaining)?;
+    }
+    BALANCES.update(
+        deps.storage,
+        &info.sender,
+        |balance: Option<Uint128>| -> StdResult<_> {
+            Ok(balance.unwrap_or_default().checked_add(amount)?)
+        },
+    )?;
+
+    // Stakes on a passed grant no longer matter so its conviction
+    // is left as it was when it passed.
+    let pending = grant.status == ProposalStatus::Pending;
+    let config = load_config(deps.storage)?;
+    if pending {
+        grant.conviction = config.conviction_at(grant, env.block.height);
+        grant.last_update = env.block.height;
+    }
+    grant.staked = grant.staked.checked_sub(amount).map_err(StdError::from)?;
+    GRANTS.save(deps.storage, &grants)?;
+
+    // The conviction built up by the removed stake still counts. Stakes
+    // may be removed while the DAO is paused but the grant is not paid
+    // out until it is lifted.
+    let res = if pending && !STATE.load(deps.storage)?.paused {
+        try_pass(deps, env, &config, proposal_id)?
+    } else {
+        Response::new()
+    };
+    Ok(res
+        .add_attribute("method", "unstake_conviction")
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_attribute("tokens", amount))
+}
+
+pub fn execute_update(
+    deps: DepsMut,
+    env: Env,
+    proposal_id: u64,
+) -> Result<Response, ContractError> {
+    let config = load_config(deps.storage)?;
+    pause::assert_not_paused(deps.storage, None)?;
+    let grant = load_grant(deps.storage, proposal_id)?;
+    if grant.status != ProposalStatus::Pending {
+        return Err(ContractError::VoteOnCompletedProposal);
+    }
+    Ok(try_pass(deps, env, &config, proposal_id)?
+        .add_attribute("method", "update_conviction")
+        .add_attribute("proposal_id", proposal_id.to_string()))
+}
+
+/// Brings a pending grant's conviction up to date and pays it out of
+/// the treasury if the conviction has crossed the grant's threshold.
+fn try_pass(
+    deps: DepsMut,
+    env: Env,
+    config: &ConvictionConfig,
+    proposal_id: u64,
+) -> Result<Response, ContractError> {
+    let mut grants = GRANTS.load(deps.storage)?;
+    let grant = &mut grants[proposal_id as usize];
+
+    grant.conviction = config.conviction_at(grant, env.block.height);
+    grant.last_update = env.block.height;
+
+    let supply = TOKEN_INFO.load(deps.storage)?.total_supply;
+    let treasury = BALANCES
+        .may_load(deps.storage, &env.contract.address)?
+        .unwrap_or_default();
+    let passed = matches!(
+        config.threshold(supply, treasury, grant.amount),
+        Some(threshold) if grant.conviction >= threshold
+    );
+    if passed {
+        grant.status = ProposalStatus::Passed;
+    }
+    let (recipient, amount) = (grant.recipient.to_string(), grant.amount);
+    GRANTS.save(deps.storage, &grants)?;
+
+    let res = if passed {
+        // The threshold is only defined when the grant is for less
+        // than the treasury holds so this can not fail.
+        treasury::transfer(deps, env, recipient, amount)?
+    } else {
+        Response::new()
+    };
+    Ok(res.add_attribute("passed", passed.to_string()))
+}
+
+fn load_grant(storage: &dyn Storage, proposal_id: u64) -> StdResult<GrantProposal> {
+    GRANTS
+        .may_load(storage)?
+        .unwrap_or_default()
+        .into_iter()
+        .nth(proposal_id as usize)
+        .ok_or_else(|| not_found(proposal_id))
+}
+
+fn grant_response(id: u64, grant: GrantProposal) -> GrantResponse {
+    GrantResponse {
+        id,
+        title: grant.title,
+        body: grant.body,
+        proposer: grant.proposer,
+        recipient: grant.recipient,
+        amount: grant.amount,
+        status: grant.status,
+        staked: grant.staked,
+        conviction: grant.conviction,
+        last_update: grant.last_update,
+    }
+}
+
+pub fn query_grants(deps: Deps) -> StdResult<GrantListResponse> {
+    let grants = GRANTS
+        .may_load(deps.storage)?
+        .unwrap_or_default()
+        .into_iter()
+        .enumerate()
+        .map(|(id, grant)| grant_response(id as u64, grant))
+        .collect();
+    Ok(GrantListResponse { grants })
+}
+
+pub fn query_grant(deps: Deps, proposal_id: u64) -> StdResult<GrantResponse> {
+    let grant = load_grant(deps.storage, proposal_id)?;
+    Ok(grant_response(proposal_id, grant))
+}
+
+pub fn query_conviction(deps: Deps, env: Env, proposal_id: u64) -> StdResult<ConvictionResponse> {
+    let config = STATE
+        .load(deps.storage)?
+        .conviction
+        .ok_or_else(|| StdError::generic_err("grant proposals are not enabled"))?;
+    let grant = load_grant(deps.storage, proposal_id)?;
+
+    let conviction = if grant.status == ProposalStatus::Pending {
+        config.conviction_at(&grant, env.block.height)
+    } else {
+        grant.conviction
+    };
+    let supply = TOKEN_INFO.load(deps.storage)?.total_supply;
+    let treasury = BALANCES
+        .may_load(deps.storage, &env.contract.address)?
+        .unwrap_or_default();
+    Ok(ConvictionResponse {
+        conviction,
+        threshold: config.threshold(supply, treasury, grant.amount),
+        staked: grant.staked,
+    })
+}
@@ -0,0 +1,190 @@
+use std::collections::BTreeMap;
+
+use cosmwasm_std::{
+    Addr, BlockInfo, Decimal, Deps, DepsMut, Empty, Env, MessageInfo, Order, Response, StdResult,
+    Storage, Uint128,
+};
+
+use cw_storage_plus::U64Key;
+
+use crate::actions::split_vote;
+use crate::events::status_name;
+use crate::msg::{DelegateResponse, VotePosition, VotingPowerResponse};
+use crate::state::{
+    proposals, DelegatedVote, Proposal, ProposalStatus, BALANCES, DELEGATES, DELEGATORS, STATE,
+};
+use crate::vesting;
+use crate::ContractError;
+
+pub fn execute_delegate(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    delegate: String,
+) -> Result<Response, ContractError> {
+    let delegate = deps.api.addr_validate(&delegate)?;
+    if delegate == info.sender {
+        return Err(ContractError::SelfDelegation);
+    }
+
+    // A holder may only have one delegate at a time so clear out
+    // the reverse lookup for any old one.
+    if let Some(old) = DELEGATES.may_load(deps.storage, &info.sender)? {
+        DELEGATORS.remove(deps.storage, (&old, &info.sender));
+        drop_delegated_votes(deps.storage, &info.sender)?;
+    }
+    DELEGATES.save(deps.storage, &info.sender, &delegate)?;
+    DELEGATORS.save(deps.storage, (&delegate, &info.sender), &Empty {})?;
+
+    Ok(Response::new()
+        .add_attribute("method", "delegate")
+        .add_attribute("from", info.sender)
+        .add_attribute("to", delegate))
+}
+
+pub fn execute_undelegate(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let delegate = DELEGATES
+        .may_load(deps.storage, &info.sender)?
+        .ok_or(ContractError::NotDelegated)?;
+    DELEGATES.remove(deps.storage, &info.sender);
+    DELEGATORS.remove(deps.storage, (&delegate, &info.sender));
+    drop_delegated_votes(deps.storage, &info.sender)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "undelegate")
+        .add_attribute("from", info.sender)
+        .add_attribute("to", delegate))
+}
+
+/// Stops the votes a delegate has already cast with `delegator`'s
+/// power from counting on proposals that are still being voted on.
+/// Called when `delegator` leaves their delegate.
+fn drop_delegated_votes(storage: &mut dyn Storage, delegator: &Addr) -> StdResult<()> {
+    let pending = status_name(&ProposalStatus::Pending).as_bytes().to_vec();
+    let voted = proposals()
+        .idx
+        .status
+        .prefix(pending)
+        .range(storage, None, None, Order::Ascending)
+        .filter(|item| {
+            item.as_ref()
+                .map_or(true, |(_, p)| p.has_delegated_vote(delegator))
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    for (_, mut proposal) in voted {
+        proposal.remove_delegated_votes(delegator);
+        proposals().save(storage, U64Key::new(proposal.id), &proposal)?;
+    }
+    Ok(())
+}
+
+/// The number of tokens `address` may vote with, which is also the
+/// power they lend their delegate. Tokens that have yet to vest only
+/// count if locked tokens may vote.
+fn voting_power(storage: &dyn Storage, block: &BlockInfo, address: &Addr) -> StdResult<Uint128> {
+    let balance = BALANCES.may_load(storage, address)?.unwrap_or_default();
+    if STATE.load(storage)?.locked_tokens_can_vote {
+        return Ok(balance);
+    }
+    let locked = vesting::locked_balance(storage, block, address)?;
+    Ok(balance.saturating_sub(locked))
+}
+
+fn delegators(storage: &dyn Storage, delegate: &Addr) -> StdResult<Vec<Addr>> {
+    DELEGATORS
+        .prefix_de(delegate)
+        .keys_de(storage, None, None, Order::Ascending)
+        .collect()
+}
+
+/// Records votes on behalf of everyone who has delegated to
+/// `delegate`. Holders who have voted on the proposal themselves or
+/// who already had their power used by this delegate are skipped.
+///
+/// Delegated power is the delegator's balance at the time of the
+/// vote. See `recheck_delegated_votes` for tokens that move
+/// afterwards.
+pub(crate) fn cast_delegated_votes(
+    storage: &dyn Storage,
+    block: &BlockInfo,
+    proposal: &mut Proposal,
+    delegate: &Addr,
+    options: &[(VotePosition, Decimal)],
+) -> StdResult<()> {
+    for delegator in delegators(storage, delegate)? {
+        if proposal.has_direct_vote(&delegator) || proposal.has_delegated_vote(&delegator) {
+            continue;
+        }
+        let power = voting_power(storage, block, &delegator)?;
+        if power.is_zero() {
+            continue;
+        }
+        // Delegated power is split the same way as the delegate's
+        // own stake.
+        for (position, power) in split_vote(power, options) {
+            proposal.add_delegated_vote(DelegatedVote {
+                delegate: delegate.clone(),
+                delegator: delegator.clone(),
+                position,
+                power,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Reduces the power of the delegated votes on `proposal` to what
+/// each delegator still holds. Tokens that have moved since a
+/// delegate voted are then only counted for whoever holds them now.
+/// Called before a proposal is tallied.
+pub(crate) fn recheck_delegated_votes(
+    storage: &dyn Storage,
+    block: &BlockInfo,
+    proposal: &mut Proposal,
+) -> StdResult<()> {
+    let mut counted: BTreeMap<Addr, Uint128> = BTreeMap::new();
+    for vote in proposal.delegated.iter() {
+        *counted.entry(vote.delegator.clone()).or_default() += vote.power;
+    }
+    for (delegator, counted) in counted {
+        let held = voting_power(storage, block, &delegator)?;
+        if held >= counted {
+            continue;
+        }
+        for vote in proposal
+            .delegated
+            .iter_mut()
+            .filter(|v| v.delegator == delegator)
+        {
+            vote.power = vote.power.multiply_ratio(held, counted);
+        }
+    }
+    proposal.delegated.retain(|v| !v.power.is_zero());
+    Ok(())
+}
+
+pub fn query_delegate(deps: Deps, address: String) -> StdResult<DelegateResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let delegate = DELEGATES.may_load(deps.storage, &address)?;
+    Ok(DelegateResponse { delegate })
+}
+
+/// Counts power the same way votes do so that the result matches
+/// what a vote cast in this block would be worth.
+pub fn query_voting_power(deps: Deps, env: Env, address: String) -> StdResult<VotingPowerResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let balance = voting_power(deps.storage, &env.block, &address)?;
+    let mut delegated = Uint128::zero();
+    for delegator in delegators(deps.storage, &address)? {
+        delegated += voting_power(deps.storage, &env.block, &delegator)?;
+    }
+    Ok(VotingPowerResponse {
+        balance,
+        delegated,
+        power: balance + delegated,
+    })
+}
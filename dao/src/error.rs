@@ -1,4 +1,5 @@
 use cosmwasm_std::{StdError, Uint128};
+use cw0::PaymentError;
 use thiserror::Error;
 
 #[derive(Error, Debug, PartialEq)]
@@ -6,15 +7,102 @@ pub enum ContractError {
     #[error("{0}")]
     Std(#[from] StdError),
 
+    #[error("{0}")]
+    Payment(#[from] PaymentError),
+
     #[error("Attempt to vote or withdraw on a completed proposal")]
     VoteOnCompletedProposal,
 
     #[error("Quorum must be greater than zero and not greater than total token supply")]
     InvalidQuorum,
 
+    #[error("Veto threshold must not be greater than one")]
+    InvalidVetoThreshold,
+
     #[error("Insufficent funds for proposal. Needed ({needed}), got ({got})")]
     InsufficentProposalFunds { needed: Uint128, got: Uint128 },
 
     #[error("Can not transfer or send or vote with zero tokens")]
     InvalidZeroAmount,
+
+    #[error("Tokens are locked by a vesting schedule. Available ({available}), needed ({needed})")]
+    TokensLocked { available: Uint128, needed: Uint128 },
+
+    #[error("Insufficent funds in treasury. Needed ({needed}), available ({available})")]
+    InsufficientTreasury { needed: Uint128, available: Uint128 },
+
+    #[error("Insufficent native funds in treasury. Needed ({needed}), available ({available})")]
+    InsufficientNativeTreasury { needed: String, available: String },
+
+    #[error("Invalid choice: {reason}")]
+    InvalidChoice { reason: String },
+
+    #[error("Vote weights must be positive, for distinct positions, and add up to one")]
+    InvalidVoteWeights,
+
+    #[error("Token transfers are disabled")]
+    TransfersDisabled,
+
+    #[error("Invalid vesting schedule: {reason}")]
+    InvalidVestingSchedule { reason: String },
+
+    #[error("Invalid conviction config: {reason}")]
+    InvalidConvictionConfig { reason: String },
+
+    #[error("Grant proposals are not enabled")]
+    ConvictionDisabled,
+
+    #[error("Pre-propose needs approvers or a sponsor threshold")]
+    InvalidPreProposeConfig,
+
+    #[error("Proposal is waiting for approval")]
+    ProposalNotApproved,
+
+    #[error("Proposal is not waiting for approval")]
+    NotAwaitingApproval,
+
+    #[error("Proposal is not being voted on")]
+    ProposalNotPending,
+
+    #[error("Proposals can not be cancelled once they have votes")]
+    CancelNotAllowed,
+
+    #[error("Cancellation fee must not be greater than one")]
+    InvalidCancelFee,
+
+    #[error("Proposal is not waiting to be executed")]
+    ProposalNotQueued,
+
+    #[error("Proposal can not be executed until {executable_at}")]
+    TimelockNotElapsed { executable_at: String },
+
+    #[error("Only addresses that voted against a queued proposal may rage quit")]
+    NotDissenter,
+
+    #[error("A rage quit is already in progress")]
+    RageQuitInProgress,
+
+    #[error("Unknown reply ID ({id})")]
+    UnknownReplyId { id: u64 },
+
+    #[error("The DAO has been paused by its guardian")]
+    Paused,
+
+    #[error("Config update does not change anything")]
+    EmptyConfigUpdate,
+
+    #[error("Config update both sets and clears a setting")]
+    ConflictingConfigUpdate,
+
+    #[error("Nothing to claim")]
+    NoRefund,
+
+    #[error("Unauthorized")]
+    Unauthorized,
+
+    #[error("Can not delegate voting power to yourself")]
+    SelfDelegation,
+
+    #[error("Sender has not delegated their voting power")]
+    NotDelegated,
 }
@@ -0,0 +1,138 @@
+//! Events emitted by the DAO. These are indexed on chain under a
+//! `wasm-` prefix, for example `wasm-proposal_created`, and their
+//! attributes are part of the contract's public interface.
+
+use cosmwasm_std::{to_vec, Addr, Event, StdError, StdResult, Uint128};
+use serde::Serialize;
+
+use crate::msg::{DaoItem, ProposeAction, VotePosition};
+use crate::state::{Proposal, ProposalStatus};
+
+/// A stable name for the kind of a proposal's action.
+pub fn action_kind(action: &ProposeAction) -> &'static str {
+    match action {
+        ProposeAction::ChangeQuorum { .. } => "change_quorum",
+        ProposeAction::ChangeProposalCost { .. } => "change_proposal_cost",
+        ProposeAction::AddItem(_) => "add_item",
+        ProposeAction::RemoveItem { .. } => "remove_item",
+        ProposeAction::UpdateConfig(_) => "update_config",
+        ProposeAction::ChangeDepositPolicy { .. } => "change_deposit_policy",
+        ProposeAction::ChangeCancelPolicy { .. } => "change_cancel_policy",
+        ProposeAction::ChangeTokenMode { .. } => "change_token_mode",
+        ProposeAction::TreasuryTransfer { .. } => "treasury_transfer",
+        ProposeAction::TreasurySend { .. } => "treasury_send",
+        ProposeAction::BankSend { .. } => "bank_send",
+        ProposeAction::CreateVesting { .. } => "create_vesting",
+        ProposeAction::Unpause => "unpause",
+        ProposeAction::ChangeGuardian { .. } => "change_guardian",
+        ProposeAction::UpdateCw20Tokens { .. } => "update_cw20_tokens",
+        ProposeAction::MultipleChoice { .. } => "multiple_choice",
+    }
+}
+
+/// A stable name for a proposal status.
+pub fn status_name(status: &ProposalStatus) -> &'static str {
+    match status {
+        ProposalStatus::Passed => "passed",
+        ProposalStatus::Failed => "failed",
+        ProposalStatus::Pending => "pending",
+        ProposalStatus::PendingApproval => "pending_approval",
+        ProposalStatus::Withdrawn => "withdrawn",
+        ProposalStatus::Cancelled => "cancelled",
+        ProposalStatus::Queued { .. } => "queued",
+    }
+}
+
+/// A stable name for a vote position.
+pub fn position_name(position: &VotePosition) -> &'static str {
+    match position {
+        VotePosition::Yes => "yes",
+        VotePosition::No => "no",
+        VotePosition::Abstain => "abstain",
+        VotePosition::NoWithVeto => "no_with_veto",
+    }
+}
+
+/// Serializes a config value as JSON.
+fn json<T: Serialize>(value: &T) -> StdResult<String> {
+    String::from_utf8(to_vec(value)?).map_err(StdError::from)
+}
+
+pub fn proposal_created(proposal_id: u64, proposal: &Proposal) -> Event {
+    Event::new("proposal_created")
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_attribute("proposer", proposal.proposer.as_str())
+        .add_attribute("title", proposal.title.as_str())
+        .add_attribute("action", action_kind(&proposal.action))
+        .add_attribute("status", status_name(&proposal.status))
+}
+
+/// Emitted for each position a vote stakes tokens on. `status` is
+/// the proposal's status once the vote has been counted.
+pub fn vote_cast(
+    proposal_id: u64,
+    voter: &Addr,
+    position: &VotePosition,
+    amount: Uint128,
+    status: &ProposalStatus,
+) -> Event {
+    Event::new("vote_cast")
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_attribute("voter", voter.as_str())
+        .add_attribute("position", position_name(position))
+        .add_attribute("amount", amount)
+        .add_attribute("status", status_name(status))
+}
+
+pub fn vote_withdrawn(proposal_id: u64, voter: &Addr, amount: Uint128) -> Event {
+    Event::new("vote_withdrawn")
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_attribute("voter", voter.as_str())
+        .add_attribute("amount", amount)
+}
+
+pub fn refund_claimed(proposal_id: u64, voter: &Addr, amount: Uint128) -> Event {
+    Event::new("refund_claimed")
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_attribute("voter", voter.as_str())
+        .add_attribute("amount", amount)
+}
+
+pub fn proposal_completed(proposal_id: u64, proposal: &Proposal) -> Event {
+    Event::new("proposal_completed")
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_attribute("status", status_name(&proposal.status))
+        .add_attribute("yes", proposal.get_votes(VotePosition::Yes))
+        .add_attribute("no", proposal.get_votes(VotePosition::No))
+        .add_attribute("abstain", proposal.get_votes(VotePosition::Abstain))
+        .add_attribute("no_with_veto", proposal.get_votes(VotePosition::NoWithVeto))
+}
+
+/// Emitted when a proposal changes status without completing: when
+/// it leaves the approval queue or when a queued proposal is
+/// executed.
+pub fn proposal_status_changed(proposal_id: u64, status: &ProposalStatus) -> Event {
+    Event::new("proposal_status_changed")
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_attribute("status", status_name(status))
+}
+
+/// `item_id` is the item's position in the item list at the time it
+/// was added. It shifts when an earlier item is removed.
+pub fn item_added(item_id: u64, item: &DaoItem) -> Event {
+    Event::new("item_added")
+        .add_attribute("item_id", item_id.to_string())
+        .add_attribute("name", item.name.as_str())
+}
+
+pub fn item_removed(item_id: u64) -> Event {
+    Event::new("item_removed").add_attribute("item_id", item_id.to_string())
+}
+
+/// Emitted when a governance setting changes. `value` is the new
+/// setting encoded as JSON.
+pub fn config_changed<T: Serialize>(key: &str, value: &T) -> StdResult<Event> {
+    Ok(Event::new("config_changed")
+        .add_attribute("key", key)
+        .add_attribute("value", json(value)?))
+}
@@ -1,8 +1,22 @@
 pub mod actions;
+pub mod approval;
+pub mod choice;
+pub mod config;
 pub mod contract;
+pub mod conviction;
+pub mod delegation;
 mod error;
+pub mod events;
 pub mod msg;
+pub mod pause;
+pub mod quadratic;
+pub mod ragequit;
+pub mod refunds;
+#[cfg(any(test, feature = "schema"))]
+pub mod schema;
 pub mod state;
 pub mod tokens;
+pub mod treasury;
+pub mod vesting;
 
 pub use crate::error::ContractError;
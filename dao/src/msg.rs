@@ -1,9 +1,13 @@
-use cosmwasm_std::{Binary, Uint128};
+use cosmwasm_std::{Addr, Binary, Coin, Decimal, Uint128};
+use cw0::Duration;
+use cw2::ContractVersion;
 use cw20::Cw20Coin;
+
+use crate::state::{BlockStamp, PreProposeConfig, ProposalStatus};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
 pub struct InstantiateMsg {
     /// The number of webdao tokens that must participate in a vote in
     /// order for it to complete.
@@ -11,12 +15,132 @@ pub struct InstantiateMsg {
     /// The number of webdao tokens that must be locked in order to
     /// create a new proposal.
     pub proposal_cost: Uint128,
+    /// Native coins that may be deposited in place of
+    /// `proposal_cost` when creating a proposal.
+    #[serde(default)]
+    pub native_proposal_cost: Option<Coin>,
 
     /// Information about the voting tokens that the DAO will use.
     pub token_info: TokenInstantiateInfo,
+
+    /// If tokens that have yet to vest may be staked on votes.
+    #[serde(default)]
+    pub locked_tokens_can_vote: bool,
+    /// When proposal deposits are returned to proposers. Defaults to
+    /// always refunding them.
+    #[serde(default)]
+    pub deposit_policy: DepositPolicy,
+    /// The fraction of the vote that must be `NoWithVeto` for a
+    /// proposal to be vetoed. Vetoes are disabled if this is not set.
+    #[serde(default)]
+    pub veto_threshold: Option<Decimal>,
+    /// If set, a voter's weight on a proposal is the square root of
+    /// the number of tokens they stake on it.
+    #[serde(default)]
+    pub quadratic: bool,
+    /// Settings for conviction voting on grants. Grant proposals are
+    /// disabled if this is not set.
+    #[serde(default)]
+    pub conviction: Option<ConvictionConfig>,
+    /// If set, new proposals wait in an approval queue until they are
+    /// approved or sponsored.
+    #[serde(default)]
+    pub pre_propose: Option<PreProposeInfo>,
+    /// When proposers may cancel their proposals. Defaults to only
+    /// before any votes are cast and without a fee.
+    #[serde(default)]
+    pub cancel_policy: CancelPolicy,
+    /// How long passed proposals wait before they may be executed.
+    /// Passed proposals are executed immediately if this is not set.
+    #[serde(default)]
+    pub timelock: Option<Duration>,
+    /// An address that may cancel passed proposals while they wait
+    /// out the timelock and pause the DAO in an emergency.
+    #[serde(default)]
+    pub guardian: Option<String>,
+    /// CW20 tokens held by the treasury that members receive a share
+    /// of when they rage quit.
+    #[serde(default)]
+    pub cw20_tokens: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct CancelPolicy {
+    /// If proposers may cancel proposals that have been voted on.
+    pub after_votes: bool,
+    /// The fraction of the deposit that is kept by the treasury when
+    /// a proposal is cancelled.
+    pub fee: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PreProposeInfo {
+    /// Addresses that may move a proposal out of the approval queue
+    /// and into voting.
+    pub approvers: Vec<String>,
+    /// If set, a proposal also leaves the queue once this many tokens
+    /// have been staked to sponsor it.
+    pub sponsor_threshold: Option<Uint128>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ConvictionConfig {
+    /// The fraction of a grant's conviction that is kept from one
+    /// block to the next. Must be less than one.
+    pub decay: Decimal,
+    /// The largest fraction of the treasury that a single grant may
+    /// request. Grants asking for more than this can never pass.
+    pub max_ratio: Decimal,
+    /// Scales the conviction that every grant needs to pass. Must be
+    /// greater than zero.
+    pub weight: Decimal,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct GrantProposeMsg {
+    pub title: String,
+    pub body: String,
+    /// The address that will receive tokens from the treasury if the
+    /// grant passes.
+    pub recipient: String,
+    /// The number of tokens requested from the treasury.
+    pub amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct DepositPolicy {
+    /// When the proposer gets their deposit back.
+    pub refund: DepositRefund,
+    /// What happens to deposits that are not refunded.
+    pub forfeit: DepositForfeit,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DepositRefund {
+    /// Deposits are refunded no matter the outcome of the proposal.
+    #[default]
+    Always,
+    /// Deposits are only refunded if the proposal passes.
+    OnPass,
+    /// Deposits are refunded if the yes and no votes alone reached
+    /// quorum, regardless of if the proposal passed. Proposals that
+    /// only reach quorum with abstentions or vetoes lose their
+    /// deposit.
+    OnQuorum,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DepositForfeit {
+    /// Forfeited deposits are added to the DAO's treasury.
+    #[default]
+    Treasury,
+    /// Forfeited deposits are destroyed.
+    Burn,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
 pub struct TokenInstantiateInfo {
     /// The name of the token.
     pub name: String,
@@ -30,6 +154,61 @@ pub struct TokenInstantiateInfo {
     /// The initial token balances. This determins the number of
     /// tokens that will initially be in circulation.
     pub initial_balances: Vec<Cw20Coin>,
+    /// Tokens that are released to their holders over time. These
+    /// count towards the total supply but can not be transferred,
+    /// sent, or burned until they have vested.
+    #[serde(default)]
+    pub vesting: Vec<VestingGrant>,
+    /// If tokens may be moved between holders. Defaults to
+    /// transferable.
+    #[serde(default)]
+    pub mode: TokenMode,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenMode {
+    /// Tokens behave like any other CW20 token.
+    #[default]
+    Transferable,
+    /// Tokens represent membership. They can be staked on votes and
+    /// burned but can not be transferred or sent to other holders.
+    NonTransferable,
+    /// Transfers, sends, and burns are temporarily disabled. Tokens
+    /// can still be staked on votes.
+    Paused,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VestingGrant {
+    /// The address that will receive the tokens.
+    pub address: String,
+    /// How the tokens will be released.
+    pub schedule: VestingSchedule,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum VestingUnit {
+    /// Start, cliff, and end are block heights.
+    Height,
+    /// Start, cliff, and end are unix timestamps in seconds.
+    Time,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VestingSchedule {
+    /// The number of tokens being vested.
+    pub amount: Uint128,
+    pub unit: VestingUnit,
+    /// When tokens begin to vest.
+    pub start: u64,
+    /// No tokens may be used before the cliff. Once it has passed
+    /// all of the tokens that have vested since start are released.
+    pub cliff: u64,
+    /// When all of the tokens will have vested. Between start and
+    /// end tokens vest linearly.
+    pub end: u64,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -59,8 +238,98 @@ pub enum ProposeAction {
 
     /// Proposes that a new webpage be added.
     AddItem(DaoItem),
-    /// Proposes that an existinig webpage be removed.
+    /// Proposes that an existinig webpage be removed. `id` is the
+    /// item's current position in the item list, see `ItemResponse`.
     RemoveItem { id: u64 },
+
+    /// Proposes that several governance settings be changed at once.
+    UpdateConfig(ConfigUpdate),
+
+    /// Proposes that the policy for refunding proposal deposits be
+    /// changed.
+    ChangeDepositPolicy { policy: DepositPolicy },
+
+    /// Proposes that the policy for cancelling proposals be changed.
+    ChangeCancelPolicy { policy: CancelPolicy },
+
+    /// Proposes that the token's transfer mode be changed.
+    ChangeTokenMode { mode: TokenMode },
+
+    /// Proposes that tokens from the DAO's treasury be transferred to
+    /// an address.
+    TreasuryTransfer { recipient: String, amount: Uint128 },
+    /// Proposes that tokens from the DAO's treasury be sent to a
+    /// contract along with a message for that contract to execute.
+    TreasurySend {
+        contract: String,
+        amount: Uint128,
+        msg: Binary,
+    },
+
+    /// Proposes that native coins from the DAO's treasury be sent to
+    /// an address.
+    BankSend { to: String, amount: Vec<Coin> },
+
+    /// Proposes that new tokens be minted to an address and released
+    /// according to a vesting schedule.
+    CreateVesting {
+        address: String,
+        schedule: VestingSchedule,
+    },
+
+    /// Proposes that a pause put in place by the guardian be lifted.
+    /// This may be proposed, voted on, and executed while the DAO is
+    /// paused.
+    Unpause,
+    /// Proposes that the guardian be replaced or, if None, removed.
+    /// This may be proposed, voted on, and executed while the DAO is
+    /// paused.
+    ChangeGuardian { guardian: Option<String> },
+
+    /// Proposes that CW20 tokens be added to or removed from the
+    /// list of tokens paid out on rage quit.
+    UpdateCw20Tokens {
+        add: Vec<String>,
+        remove: Vec<String>,
+    },
+
+    /// Proposes several choices, each with its own action. Voters
+    /// rank the choices they support and the winner is decided by
+    /// instant runoff. The winning choice's action is executed.
+    MultipleChoice { choices: Vec<Choice> },
+}
+
+/// Governance settings to change. Settings that are not set are
+/// left as they are. Optional settings are removed with the matching
+/// `clear_` flag.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct ConfigUpdate {
+    pub quorum: Option<Uint128>,
+    pub proposal_cost: Option<Uint128>,
+    pub native_proposal_cost: Option<Coin>,
+    pub locked_tokens_can_vote: Option<bool>,
+    pub deposit_policy: Option<DepositPolicy>,
+    pub veto_threshold: Option<Decimal>,
+    pub quadratic: Option<bool>,
+    pub cancel_policy: Option<CancelPolicy>,
+    pub timelock: Option<Duration>,
+
+    /// Stops accepting native coins as proposal deposits.
+    #[serde(default)]
+    pub clear_native_proposal_cost: bool,
+    /// Stops proposals from being vetoed.
+    #[serde(default)]
+    pub clear_veto_threshold: bool,
+    /// Executes passed proposals right away.
+    #[serde(default)]
+    pub clear_timelock: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Choice {
+    pub title: String,
+    /// The action to execute if this choice wins, if any.
+    pub action: Option<ProposeAction>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -84,6 +353,11 @@ pub enum VotePosition {
     /// count towards the quorum requirements and I trust that those
     /// with positions will decide on a reasonable outcome.
     Abstain,
+    /// I would not like to execute the proposal and believe it
+    /// should not have been made. Counts as a no vote. If enough of
+    /// the vote is a veto the proposal fails and its deposit is
+    /// forfeited.
+    NoWithVeto,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -93,8 +367,15 @@ pub struct VoteMsg {
     pub proposal_id: u64,
     /// What position that sender would like to lock their tokens to.
     pub position: VotePosition,
-    /// The number of tokens that should be staked to this vote.
+    /// The number of tokens that should be staked to this vote. May
+    /// be zero for a delegate voting with only the power delegated to
+    /// them.
     pub amount: Uint128,
+    /// For multiple choice proposals, the indexes of the choices the
+    /// sender supports in order of preference. Required when voting
+    /// yes on them and must be empty otherwise.
+    #[serde(default)]
+    pub ranking: Vec<u32>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -103,11 +384,86 @@ pub enum ExecuteMsg {
     /// Provides a means via which token holders can unlock tokens
     /// that have been comitted to a proposal.
     Withdraw(WithdrawVoteMsg),
+    /// Withdraw the sender's votes from all of the proposals they
+    /// have voted on that are still being voted on. At most `limit`
+    /// of the sender's stakes after `start_after` are looked at so
+    /// that this stays within gas limits. The last proposal looked at
+    /// is returned in the `last_proposal_id` attribute to continue
+    /// from.
+    WithdrawAll {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Claim back the tokens staked on a proposal that is no longer
+    /// being voted on.
+    ClaimRefund { proposal_id: u64 },
 
-    /// Create a new proposal
+    /// Create a new proposal. If the DAO has a native proposal cost
+    /// it may be paid by sending it along with this message in place
+    /// of the usual token deposit.
     Propose(ProposeMsg),
     /// Vote on an existing proposal
     Vote(VoteMsg),
+    /// Vote on an existing proposal, splitting the staked tokens
+    /// between several positions. Weights must add up to one.
+    VoteWeighted {
+        proposal_id: u64,
+        amount: Uint128,
+        options: Vec<(VotePosition, Decimal)>,
+    },
+
+    /// Cancel a proposal that is being voted on. Only the proposer
+    /// may do this. All votes may be claimed back, and the deposit is
+    /// refunded less any cancellation fee. The guardian may also cancel passed
+    /// proposals that are waiting out the timelock.
+    Cancel { proposal_id: u64 },
+    /// Execute a passed proposal once its timelock has elapsed.
+    /// Anyone may do this.
+    ExecuteProposal { proposal_id: u64 },
+
+    /// Pause proposal creation, approval, sponsorship, voting,
+    /// cancellation, and execution, and if `transfers` is set token
+    /// transfers too. Only the guardian may do this and only a
+    /// proposal can lift the pause.
+    Pause { transfers: bool },
+
+    /// Burn tokens in exchange for a proportional share of the
+    /// treasury's native coins and registered CW20 tokens. Only
+    /// addresses that voted against a proposal that is waiting out
+    /// its timelock may do this.
+    RageQuit { amount: Uint128 },
+
+    /// Move a proposal from the approval queue into voting. Only
+    /// approvers may do this.
+    Approve { proposal_id: u64 },
+    /// Stake tokens to sponsor a proposal in the approval queue. The
+    /// proposal is opened for voting once enough tokens sponsor it.
+    /// Sponsorships are returned when it leaves the queue.
+    Sponsor { proposal_id: u64, amount: Uint128 },
+    /// Withdraw a proposal that is waiting for approval and refund
+    /// its deposit. Only the proposer may do this.
+    WithdrawProposal { proposal_id: u64 },
+
+    /// Create a new grant proposal which is decided by conviction
+    /// voting.
+    ProposeGrant(GrantProposeMsg),
+    /// Stake tokens on a grant proposal. Staked tokens build
+    /// conviction for the grant every block until they are
+    /// unstaked. If the grant has enough conviction it is paid out.
+    StakeConviction { proposal_id: u64, amount: Uint128 },
+    /// Remove tokens staked on a grant proposal.
+    UnstakeConviction { proposal_id: u64, amount: Uint128 },
+    /// Bring a grant's conviction up to date and pay it out if it
+    /// has enough.
+    UpdateConviction { proposal_id: u64 },
+
+    /// Delegate the sender's voting power to another address. When
+    /// the delegate votes on a proposal the sender's balance counts
+    /// towards the delegate's position unless the sender votes on
+    /// that proposal themselves.
+    Delegate { delegate: String },
+    /// Remove the sender's delegation.
+    Undelegate,
 
     /// Move tokens to another account without triggering actions
     Transfer { recipient: String, amount: Uint128 },
@@ -125,26 +481,355 @@ pub enum ExecuteMsg {
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
-    /// Paginated listing of proposals.
+    /// List all proposals. Returns a ProposalListResponse.
     ListProposals,
-    /// Get title, body, and action information for a proposal given
-    /// it's proposal ID.
+    /// Get a proposal given its proposal ID. Returns a
+    /// ProposalResponse.
     GetProposal { proposal_id: u64 },
+    /// List proposals with a status in order of ID, starting after
+    /// `start_after`. Queued proposals match whatever their
+    /// `executable_at`. Returns a ProposalListResponse.
+    ListProposalsByStatus {
+        status: ProposalStatus,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// List the proposals created by `proposer` in order of ID,
+    /// starting after `start_after`. Returns a
+    /// ProposalListResponse.
+    ListProposalsByProposer {
+        proposer: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// List proposals newest first, starting before
+    /// `start_before`. Returns a ProposalListResponse.
+    ReverseProposals {
+        start_before: Option<u64>,
+        limit: Option<u32>,
+    },
 
     /// List all of the items that have been added to the DAO.
+    /// Returns an ItemListResponse.
     ListItems,
+    /// Get the number of tokens backing each choice of a multiple
+    /// choice proposal as a first preference along with the choice
+    /// that would win if the vote ended now. Returns a
+    /// ChoiceTallyResponse.
+    ChoiceTally { proposal_id: u64 },
+    /// Get how many tokens an address has staked on each position
+    /// of a proposal. Returns a VoteResponse.
+    GetVote { proposal_id: u64, voter: String },
+    /// List the votes that `voter` has staked on proposals in order
+    /// of proposal ID, starting after `start_after`. `limit` is the
+    /// number of proposals to return votes for. Returns a
+    /// VoterVoteListResponse.
+    ListVotesByVoter {
+        voter: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// List the refunds that `address` may claim from proposals
+    /// that are no longer being voted on. `limit` is the number of
+    /// stakes after `start_after` to look at. Returns a
+    /// ClaimableRefundListResponse.
+    ClaimableRefunds {
+        address: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+
+    /// List all grant proposals. Returns a GrantListResponse.
+    ListGrants,
+    /// Get a grant proposal given its ID. Returns a GrantResponse.
+    GetGrant { proposal_id: u64 },
+    /// Get a grant's conviction as of the current block and the
+    /// conviction it needs to pass. Returns a ConvictionResponse.
+    Conviction { proposal_id: u64 },
+
+    /// List proposals that are waiting for approval. Returns an
+    /// ApprovalQueueResponse.
+    ApprovalQueue,
+
     /// Get all of the items that have been added to the DAO.
     GetItem { item_id: u64 },
 
-    /// Get information about what the current quorum is.
+    /// Get all of the DAO's governance settings along with the
+    /// contract's version. Returns a ConfigResponse.
+    Config,
+    /// Get information about what the current quorum is. Returns a
+    /// QuorumResponse.
     GetQuorum,
     /// Get information about what the current proposal cost is.
+    /// Returns a ProposalCostResponse.
     GetProposalCost,
 
     /// Ask the contract how many tokens a particular address
     /// controls.
     Balance { address: String },
+    /// Ask the contract how many tokens a particular address has
+    /// locked on proposals and grants. Returns a BalanceResponse.
+    StakedBalance { address: String },
+    /// Ask the contract how many tokens the DAO holds in its
+    /// treasury. This does not include tokens that are staked on
+    /// proposals. Returns a BalanceResponse.
+    TreasuryBalance,
+    /// Ask the contract what native coins the DAO holds in its
+    /// treasury. This does not include native proposal deposits.
+    /// Returns a TreasuryBalancesResponse.
+    TreasuryBalances,
+    /// List the CW20 tokens that are paid out on rage quit. Returns
+    /// a Cw20TokensResponse.
+    Cw20Tokens,
     /// Get info about the token. Returns a TokenInfoResponse
     /// containing {name, ticker, decimal, total_supply}.
     TokenInfo,
+
+    /// Get the token's current transfer mode. Returns a
+    /// TokenModeResponse.
+    TokenMode,
+
+    /// Get information about the tokens an address has vesting.
+    /// Returns a VestingInfoResponse.
+    VestingInfo { address: String },
+
+    /// Get whether the DAO is paused and who its guardian is.
+    /// Returns a PauseInfoResponse.
+    PauseInfo,
+
+    /// Get the address that a token holder has delegated their
+    /// voting power to. Returns a DelegateResponse.
+    Delegate { address: String },
+    /// Get the voting power of an address. This is the address' own
+    /// balance plus the balances of everyone who has delegated to
+    /// it, less any tokens that may not vote yet. Returns a
+    /// VotingPowerResponse.
+    VotingPower { address: String },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ProposalResponse {
+    pub id: u64,
+    pub title: String,
+    pub body: String,
+    pub action: ProposeAction,
+    pub proposer: Addr,
+    pub status: ProposalStatus,
+    /// If the proposal is queued and its timelock has elapsed as of
+    /// the current block, so that it may be executed.
+    pub executable: bool,
+    pub tally: ProposalTally,
+    /// The winning choice of a multiple choice proposal once it has
+    /// been decided.
+    pub winner: Option<u32>,
+    /// The number of voting tokens deposited to create the proposal.
+    pub proposal_cost: Uint128,
+    /// Native coins deposited in place of `proposal_cost`, if any.
+    pub native_deposit: Option<Coin>,
+    /// When the proposal was created.
+    pub created: BlockStamp,
+    /// When the proposal stopped taking votes because it passed,
+    /// failed, or was cancelled or withdrawn.
+    pub completed: Option<BlockStamp>,
+}
+
+/// The number of tokens on each position of a proposal, including
+/// delegated voting power.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ProposalTally {
+    pub yes: Uint128,
+    pub no: Uint128,
+    pub abstain: Uint128,
+    pub no_with_veto: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ProposalListResponse {
+    pub proposals: Vec<ProposalResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ItemResponse {
+    /// The item's position in the item list. Removing an item moves
+    /// every item after it down by one so ids are only stable until
+    /// the next `RemoveItem` executes.
+    pub id: u64,
+    pub name: String,
+    pub contents: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ItemListResponse {
+    pub items: Vec<ItemResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct GrantResponse {
+    pub id: u64,
+    pub title: String,
+    pub body: String,
+    pub proposer: Addr,
+    pub recipient: Addr,
+    pub amount: Uint128,
+    pub status: ProposalStatus,
+    /// The number of tokens currently staked on the grant.
+    pub staked: Uint128,
+    /// The grant's conviction as of `last_update`. The Conviction
+    /// query returns it as of the current block.
+    pub conviction: Uint128,
+    pub last_update: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct GrantListResponse {
+    pub grants: Vec<GrantResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct QuorumResponse {
+    pub quorum: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ProposalCostResponse {
+    pub proposal_cost: Uint128,
+    /// Native coins that may be deposited in place of
+    /// `proposal_cost`.
+    pub native_proposal_cost: Option<Coin>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TokenModeResponse {
+    pub mode: TokenMode,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ApprovalQueueResponse {
+    /// Proposals waiting for approval.
+    pub proposals: Vec<ProposalResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ChoiceTallyResponse {
+    /// The weight of the votes ranking each choice first, indexed by
+    /// choice. This is the number of tokens unless the proposal is
+    /// quadratic.
+    pub first_choices: Vec<Uint128>,
+    /// The instant runoff winner, if any choice has support.
+    pub winner: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VoteResponse {
+    /// The positions the voter has staked on and the number of
+    /// tokens staked on each.
+    pub votes: Vec<(VotePosition, Uint128)>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VoterVoteResponse {
+    pub proposal_id: u64,
+    pub position: VotePosition,
+    pub amount: Uint128,
+    /// If the voter's stake on the proposal has yet to be returned
+    /// to them.
+    pub locked: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VoterVoteListResponse {
+    /// One entry for each position the voter has staked on.
+    pub votes: Vec<VoterVoteResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ClaimableRefundResponse {
+    pub proposal_id: u64,
+    pub amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ClaimableRefundListResponse {
+    pub refunds: Vec<ClaimableRefundResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ConvictionResponse {
+    /// The grant's conviction as of the current block.
+    pub conviction: Uint128,
+    /// The conviction the grant needs to pass given the current
+    /// treasury. None if the grant asks for too much of the treasury
+    /// to be able to pass.
+    pub threshold: Option<Uint128>,
+    /// The number of tokens staked on the grant.
+    pub staked: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TreasuryBalancesResponse {
+    pub balances: Vec<Coin>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Cw20TokensResponse {
+    pub tokens: Vec<Addr>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VestingInfoResponse {
+    pub schedules: Vec<VestingSchedule>,
+    /// The number of tokens across all schedules that have vested.
+    pub vested: Uint128,
+    /// The number of tokens across all schedules that have yet to
+    /// vest.
+    pub locked: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ConfigResponse {
+    pub quorum: Uint128,
+    pub proposal_cost: Uint128,
+    pub native_proposal_cost: Option<Coin>,
+    pub locked_tokens_can_vote: bool,
+    pub deposit_policy: DepositPolicy,
+    pub veto_threshold: Option<Decimal>,
+    pub quadratic: bool,
+    pub conviction: Option<ConvictionConfig>,
+    pub pre_propose: Option<PreProposeConfig>,
+    pub cancel_policy: CancelPolicy,
+    pub timelock: Option<Duration>,
+    pub guardian: Option<Addr>,
+    pub paused: bool,
+    pub transfers_paused: bool,
+    pub token_mode: TokenMode,
+    /// The contract's name and version as recorded by cw2.
+    pub version: ContractVersion,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PauseInfoResponse {
+    /// If proposal creation, approval, sponsorship, voting,
+    /// cancellation, and execution are paused.
+    pub paused: bool,
+    /// If token transfers are paused.
+    pub transfers_paused: bool,
+    pub guardian: Option<Addr>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DelegateResponse {
+    /// The address that voting power has been delegated to, if any.
+    pub delegate: Option<Addr>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VotingPowerResponse {
+    /// The address' own token balance, less any tokens that may not
+    /// vote yet.
+    pub balance: Uint128,
+    /// The sum of the balances of the addresses that have delegated
+    /// to this address, less any tokens that may not vote yet.
+    pub delegated: Uint128,
+    /// balance + delegated.
+    pub power: Uint128,
 }
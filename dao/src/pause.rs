@@ -0,0 +1,68 @@
+use cosmwasm_std::{Addr, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Storage};
+
+use crate::msg::{PauseInfoResponse, ProposeAction};
+use crate::state::STATE;
+use crate::ContractError;
+
+/// If `action` may still be proposed, voted on, and executed while
+/// the DAO is paused. Without these governance could never lift a
+/// pause.
+fn is_recovery(action: &ProposeAction) -> bool {
+    matches!(
+        action,
+        ProposeAction::Unpause | ProposeAction::ChangeGuardian { .. }
+    )
+}
+
+/// Errors if the DAO is paused. `action` is the action of the
+/// proposal being created, voted on, or executed, if any.
+pub(crate) fn assert_not_paused(
+    storage: &dyn Storage,
+    action: Option<&ProposeAction>,
+) -> Result<(), ContractError> {
+    if STATE.load(storage)?.paused && !action.is_some_and(is_recovery) {
+        return Err(ContractError::Paused);
+    }
+    Ok(())
+}
+
+/// Errors if the guardian has paused transfers. The contract itself
+/// may still move tokens.
+pub(crate) fn assert_transfers_not_paused(
+    storage: &dyn Storage,
+    env: &Env,
+    sender: &Addr,
+) -> Result<(), ContractError> {
+    if *sender != env.contract.address && STATE.load(storage)?.transfers_paused {
+        return Err(ContractError::Paused);
+    }
+    Ok(())
+}
+
+pub fn execute_pause(
+    deps: DepsMut,
+    info: MessageInfo,
+    transfers: bool,
+) -> Result<Response, ContractError> {
+    let mut state = STATE.load(deps.storage)?;
+    if state.guardian.as_ref() != Some(&info.sender) {
+        return Err(ContractError::Unauthorized);
+    }
+    state.paused = true;
+    // A pause may be widened but only governance may narrow it.
+    state.transfers_paused |= transfers;
+    STATE.save(deps.storage, &state)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "pause")
+        .add_attribute("transfers", state.transfers_paused.to_string()))
+}
+
+pub fn query_pause_info(deps: Deps) -> StdResult<PauseInfoResponse> {
+    let state = STATE.load(deps.storage)?;
+    Ok(PauseInfoResponse {
+        paused: state.paused,
+        transfers_paused: state.transfers_paused,
+        guardian: state.guardian,
+    })
+}
@@ -0,0 +1,91 @@
+use std::collections::BTreeMap;
+
+use cosmwasm_std::{Addr, StdResult, Uint128};
+
+use crate::msg::VotePosition;
+use crate::state::Proposal;
+
+/// The integer square root of `n`, rounded down.
+pub fn isqrt(n: Uint128) -> Uint128 {
+    let n = n.u128();
+    if n < 2 {
+        return Uint128::from(n);
+    }
+    // Newton's method. The first guess is n / 2 + 1 so it can not
+    // overflow and is always above the root.
+    let mut x0 = n / 2 + 1;
+    let mut x1 = (x0 + n / x0) / 2;
+    while x1 < x0 {
+        x0 = x1;
+        x1 = (x0 + n / x0) / 2;
+    }
+    Uint128::from(x0)
+}
+
+/// Every stake on the proposal, including power used by a delegate
+/// on a delegator's behalf, along with the position it is on.
+fn stakes(proposal: &Proposal) -> impl Iterator<Item = (&Addr, VotePosition, Uint128)> {
+    let direct = [
+        (VotePosition::Yes, &proposal.yes),
+        (VotePosition::No, &proposal.no),
+        (VotePosition::Abstain, &proposal.abstain),
+        (VotePosition::NoWithVeto, &proposal.no_with_veto),
+    ];
+    IntoIterator::into_iter(direct)
+        .flat_map(|(p, votes)| {
+            votes
+                .iter()
+                .map(move |(voter, amount)| (voter, p.clone(), *amount))
+        })
+        .chain(
+            proposal
+                .delegated
+                .iter()
+                .map(|d| (&d.delegator, d.position.clone(), d.power)),
+        )
+}
+
+/// Each voter's total stake on the proposal.
+pub(crate) fn voter_stakes(proposal: &Proposal) -> StdResult<BTreeMap<&Addr, Uint128>> {
+    let mut totals: BTreeMap<&Addr, Uint128> = BTreeMap::new();
+    for (voter, _, amount) in stakes(proposal) {
+        let total = totals.entry(voter).or_default();
+        *total = total.checked_add(amount)?;
+    }
+    Ok(totals)
+}
+
+/// The part of a voter's quadratic weight carried by `amount` tokens
+/// of their `total` stake.
+pub(crate) fn share(amount: Uint128, total: Uint128) -> Uint128 {
+    if total.is_zero() {
+        return Uint128::zero();
+    }
+    isqrt(total).multiply_ratio(amount, total)
+}
+
+/// The effective number of votes on `position` when votes are
+/// weighted quadratically.
+///
+/// Each voter's stakes on the proposal, including power used on
+/// their behalf by a delegate, are added up before taking the square
+/// root so splitting a stake over many votes gains nothing. The
+/// voter's weight is then shared between the positions they staked
+/// on in proportion to their stake on each.
+pub(crate) fn quadratic_votes(proposal: &Proposal, position: VotePosition) -> StdResult<Uint128> {
+    let totals = voter_stakes(proposal)?;
+    let mut on_position: BTreeMap<&Addr, Uint128> = BTreeMap::new();
+    for (voter, p, amount) in stakes(proposal) {
+        if p == position {
+            let stake = on_position.entry(voter).or_default();
+            *stake = stake.checked_add(amount)?;
+        }
+    }
+
+    on_position
+        .iter()
+        .try_fold(Uint128::zero(), |sum, (voter, stake)| {
+            let total = totals.get(voter).copied().unwrap_or_default();
+            Ok(sum.checked_add(share(*stake, total))?)
+        })
+}
@@ -0,0 +1,113 @@
+use cosmwasm_std::{
+    to_binary, Addr, BankMsg, Coin, DepsMut, Env, MessageInfo, Order, Response, StdError,
+    StdResult, Storage, SubMsg, Uint128, WasmMsg,
+};
+use cw20::Cw20ExecuteMsg;
+
+use crate::events::status_name;
+use crate::msg::VotePosition;
+use crate::state::{proposals, ProposalStatus, BALANCES, RAGE_QUIT_LOCK, TOKEN_INFO};
+use crate::tokens;
+use crate::treasury;
+use crate::ContractError;
+
+/// Reply ID for the last payout of a rage quit.
+pub const RAGE_QUIT_REPLY_ID: u64 = 1;
+
+/// If `addr` voted against a proposal that is waiting out its
+/// timelock.
+fn is_dissenter(storage: &dyn Storage, addr: &Addr) -> StdResult<bool> {
+    let queued = status_name(&ProposalStatus::Queued {
+        executable_at: Default::default(),
+    });
+    for item in proposals()
+        .idx
+        .status
+        .prefix(queued.as_bytes().to_vec())
+        .range(storage, None, None, Order::Ascending)
+    {
+        let (_, proposal) = item?;
+        if proposal
+            .votes_of(addr)
+            .iter()
+            .any(|(position, _)| matches!(position, VotePosition::No | VotePosition::NoWithVeto))
+        {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+pub fn execute_rage_quit(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    if RAGE_QUIT_LOCK.may_load(deps.storage)?.unwrap_or_default() {
+        return Err(ContractError::RageQuitInProgress);
+    }
+    if !is_dissenter(deps.storage, &info.sender)? {
+        return Err(ContractError::NotDissenter);
+    }
+
+    // Tokens held by the treasury have no claim on it so shares are
+    // taken of the tokens held by everyone else.
+    let supply = TOKEN_INFO.load(deps.storage)?.total_supply;
+    let treasury = BALANCES
+        .may_load(deps.storage, &env.contract.address)?
+        .unwrap_or_default();
+    let outstanding = supply.checked_sub(treasury).map_err(StdError::from)?;
+
+    let native = treasury::native_balances(deps.as_ref(), &env)?;
+    let cw20s = treasury::cw20_balances(deps.as_ref(), &env)?;
+    let res = tokens::execute_burn(deps.branch(), env, info.clone(), amount)?;
+
+    // The burn succeeded so outstanding >= amount > 0.
+    let mut messages = vec![];
+    let coins: Vec<Coin> = native
+        .into_iter()
+        .map(|c| Coin {
+            amount: c.amount.multiply_ratio(amount, outstanding),
+            denom: c.denom,
+        })
+        .filter(|c| !c.amount.is_zero())
+        .collect();
+    if !coins.is_empty() {
+        messages.push(SubMsg::new(BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: coins,
+        }));
+    }
+    for (token, balance) in cw20s {
+        let share = balance.multiply_ratio(amount, outstanding);
+        if share.is_zero() {
+            continue;
+        }
+        messages.push(SubMsg::new(WasmMsg::Execute {
+            contract_addr: token.into_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: info.sender.to_string(),
+                amount: share,
+            })?,
+            funds: vec![],
+        }));
+    }
+
+    // Payouts may call back into the contract. Refuse further rage
+    // quits until the last payout has been sent.
+    if let Some(last) = messages.pop() {
+        RAGE_QUIT_LOCK.save(deps.storage, &true)?;
+        messages.push(SubMsg::reply_on_success(last.msg, RAGE_QUIT_REPLY_ID));
+    }
+
+    Ok(res
+        .add_submessages(messages)
+        .add_attribute("method", "rage_quit"))
+}
+
+/// Releases the rage quit lock once all payouts have been sent.
+pub fn reply_rage_quit(deps: DepsMut) -> Result<Response, ContractError> {
+    RAGE_QUIT_LOCK.save(deps.storage, &false)?;
+    Ok(Response::new().add_attribute("method", "rage_quit_reply"))
+}
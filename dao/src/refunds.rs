@@ -0,0 +1,82 @@
+use cosmwasm_std::{
+    Addr, Deps, DepsMut, Env, Event, MessageInfo, Order, Response, StdResult, Storage, Uint128,
+};
+use cw_storage_plus::{Bound, U64Key};
+
+use crate::actions::{load_proposal, DEFAULT_LIMIT, MAX_LIMIT};
+use crate::events;
+use crate::msg::{ClaimableRefundListResponse, ClaimableRefundResponse};
+use crate::state::{ProposalStatus, STAKED};
+use crate::tokens;
+use crate::ContractError;
+
+/// The number of tokens `voter` may claim back from a proposal. Votes
+/// stay staked once a proposal stops taking votes until they are
+/// claimed. Proposal deposits are settled when the proposal completes
+/// so anything left staked is the voter's own.
+fn claimable(storage: &dyn Storage, voter: &Addr, proposal_id: u64) -> StdResult<Uint128> {
+    let staked = STAKED
+        .may_load(storage, (voter, U64Key::new(proposal_id)))?
+        .unwrap_or_default();
+    if staked.is_zero() {
+        return Ok(staked);
+    }
+    let status = load_proposal(storage, proposal_id)?.status;
+    Ok(match status {
+        ProposalStatus::Pending | ProposalStatus::PendingApproval => Uint128::zero(),
+        _ => staked,
+    })
+}
+
+/// Returns the tokens `voter` has staked on a completed proposal.
+fn claim(storage: &mut dyn Storage, voter: &Addr, proposal_id: u64) -> StdResult<Option<Event>> {
+    let amount = claimable(storage, voter, proposal_id)?;
+    if amount.is_zero() {
+        return Ok(None);
+    }
+    tokens::unstake(storage, voter, proposal_id, amount)?;
+    Ok(Some(events::refund_claimed(proposal_id, voter, amount)))
+}
+
+pub fn execute_claim_refund(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    proposal_id: u64,
+) -> Result<Response, ContractError> {
+    let event = claim(deps.storage, &info.sender, proposal_id)?.ok_or(ContractError::NoRefund)?;
+    Ok(Response::new()
+        .add_event(event)
+        .add_attribute("method", "claim_refund")
+        .add_attribute("proposal_id", proposal_id.to_string()))
+}
+
+/// Lists the refunds `address` may claim. `limit` is the number of
+/// stakes after `start_after` to look at.
+pub fn query_claimable_refunds(
+    deps: Deps,
+    address: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<ClaimableRefundListResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive_int);
+    let ids = STAKED
+        .prefix_de(&address)
+        .keys_de(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<u64>>>()?;
+
+    let mut refunds = vec![];
+    for proposal_id in ids {
+        let amount = claimable(deps.storage, &address, proposal_id)?;
+        if !amount.is_zero() {
+            refunds.push(ClaimableRefundResponse {
+                proposal_id,
+                amount,
+            });
+        }
+    }
+    Ok(ClaimableRefundListResponse { refunds })
+}
@@ -0,0 +1,48 @@
+//! The JSON schema of the contract's messages, state, and query
+//! responses. Shared by `examples/schema.rs` and the test that checks
+//! that the committed schema is in sync.
+
+use std::path::Path;
+
+use cosmwasm_schema::{export_schema, schema_for};
+use cw20::{BalanceResponse, TokenInfoResponse};
+
+use crate::msg::*;
+use crate::state::*;
+
+/// Writes a schema file for each exported type to `out_dir`.
+pub fn export_schemas(out_dir: &Path) {
+    export_schema(&schema_for!(InstantiateMsg), out_dir);
+    export_schema(&schema_for!(ExecuteMsg), out_dir);
+    export_schema(&schema_for!(QueryMsg), out_dir);
+    export_schema(&schema_for!(State), out_dir);
+    export_schema(&schema_for!(Proposal), out_dir);
+    export_schema(&schema_for!(GrantProposal), out_dir);
+    export_schema(&schema_for!(DaoItem), out_dir);
+
+    // Query responses.
+    export_schema(&schema_for!(ProposalResponse), out_dir);
+    export_schema(&schema_for!(ProposalListResponse), out_dir);
+    export_schema(&schema_for!(ItemResponse), out_dir);
+    export_schema(&schema_for!(ItemListResponse), out_dir);
+    export_schema(&schema_for!(GrantResponse), out_dir);
+    export_schema(&schema_for!(GrantListResponse), out_dir);
+    export_schema(&schema_for!(ConvictionResponse), out_dir);
+    export_schema(&schema_for!(ChoiceTallyResponse), out_dir);
+    export_schema(&schema_for!(VoteResponse), out_dir);
+    export_schema(&schema_for!(VoterVoteListResponse), out_dir);
+    export_schema(&schema_for!(ClaimableRefundListResponse), out_dir);
+    export_schema(&schema_for!(ApprovalQueueResponse), out_dir);
+    export_schema(&schema_for!(ConfigResponse), out_dir);
+    export_schema(&schema_for!(QuorumResponse), out_dir);
+    export_schema(&schema_for!(ProposalCostResponse), out_dir);
+    export_schema(&schema_for!(BalanceResponse), out_dir);
+    export_schema(&schema_for!(TreasuryBalancesResponse), out_dir);
+    export_schema(&schema_for!(Cw20TokensResponse), out_dir);
+    export_schema(&schema_for!(TokenInfoResponse), out_dir);
+    export_schema(&schema_for!(TokenModeResponse), out_dir);
+    export_schema(&schema_for!(VestingInfoResponse), out_dir);
+    export_schema(&schema_for!(DelegateResponse), out_dir);
+    export_schema(&schema_for!(PauseInfoResponse), out_dir);
+    export_schema(&schema_for!(VotingPowerResponse), out_dir);
+}
@@ -1,15 +1,54 @@
-use cosmwasm_std::{Addr, Uint128};
+use cosmwasm_std::{Addr, BlockInfo, Coin, Decimal, Empty, StdResult, Timestamp, Uint128};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cw_storage_plus::{Item, Map};
+use cw0::{Duration, Expiration};
+use cw_storage_plus::{Index, IndexList, IndexedMap, Item, Map, MultiIndex, U64Key};
 
-use crate::msg::{DaoItem, ProposeAction, ProposeMsg, VotePosition};
+use crate::events::status_name;
+use crate::msg::{
+    CancelPolicy, ConvictionConfig, DaoItem, DepositPolicy, ProposeAction, ProposeMsg, TokenMode,
+    VestingSchedule, VotePosition,
+};
+use crate::quadratic::quadratic_votes;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct State {
     pub quorum: Uint128,
     pub proposal_cost: Uint128,
+    /// Native coins that may be deposited in place of
+    /// `proposal_cost`.
+    pub native_proposal_cost: Option<Coin>,
+    /// If tokens that have yet to vest may be staked on votes.
+    pub locked_tokens_can_vote: bool,
+    /// When proposal deposits are returned to proposers.
+    pub deposit_policy: DepositPolicy,
+    /// The fraction of the vote that must be `NoWithVeto` for a
+    /// proposal to be vetoed.
+    pub veto_threshold: Option<Decimal>,
+    /// If new proposals weight votes quadratically.
+    pub quadratic: bool,
+    /// Settings for conviction voting on grants.
+    pub conviction: Option<ConvictionConfig>,
+    /// Settings for the proposal approval queue.
+    pub pre_propose: Option<PreProposeConfig>,
+    /// When proposers may cancel their proposals.
+    pub cancel_policy: CancelPolicy,
+    /// How long passed proposals wait before they may be executed.
+    pub timelock: Option<Duration>,
+    /// May cancel passed proposals during the timelock and pause
+    /// the DAO.
+    pub guardian: Option<Addr>,
+    /// If proposal creation, voting, and execution are paused.
+    pub paused: bool,
+    /// If token transfers are paused.
+    pub transfers_paused: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PreProposeConfig {
+    pub approvers: Vec<Addr>,
+    pub sponsor_threshold: Option<Uint128>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -23,10 +62,40 @@ pub enum ProposalStatus {
     /// The quorum requirement for this proposal has yet to be
     /// reached.
     Pending,
+    /// The proposal is waiting to be approved or sponsored before it
+    /// can be voted on.
+    PendingApproval,
+    /// The proposer withdrew the proposal before it was approved.
+    Withdrawn,
+    /// The proposer cancelled the proposal while it was being voted
+    /// on, or the guardian cancelled it during the timelock.
+    Cancelled,
+    /// The proposal passed and its action may be executed once
+    /// `executable_at` has passed.
+    Queued { executable_at: Expiration },
+}
+
+/// A point in the chain's history.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, JsonSchema)]
+pub struct BlockStamp {
+    pub height: u64,
+    pub time: Timestamp,
+}
+
+impl From<&BlockInfo> for BlockStamp {
+    fn from(block: &BlockInfo) -> Self {
+        Self {
+            height: block.height,
+            time: block.time,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Proposal {
+    /// The proposal's ID. This is its key in storage.
+    #[serde(default)]
+    pub id: u64,
     pub title: String,
     pub body: String,
     pub action: ProposeAction,
@@ -35,9 +104,83 @@ pub struct Proposal {
     pub yes: Vec<(Addr, Uint128)>,
     pub no: Vec<(Addr, Uint128)>,
     pub abstain: Vec<(Addr, Uint128)>,
+    #[serde(default)]
+    pub no_with_veto: Vec<(Addr, Uint128)>,
+    /// Rankings for multiple choice proposals. The stake on each
+    /// ballot is also counted as a yes vote.
+    #[serde(default)]
+    pub ballots: Vec<Ballot>,
+    /// Tokens staked to sponsor the proposal while it waits for
+    /// approval.
+    #[serde(default)]
+    pub sponsors: Vec<(Addr, Uint128)>,
+    /// The index of the winning choice of a multiple choice
+    /// proposal once it has completed.
+    #[serde(default)]
+    pub winner: Option<u32>,
+
+    /// Votes cast by delegates on behalf of the token holders that
+    /// have delegated to them.
+    pub delegated: Vec<DelegatedVote>,
 
     pub proposer: Addr,
     pub proposal_cost: Uint128,
+    /// Native coins deposited in place of `proposal_cost`.
+    pub native_deposit: Option<Coin>,
+    /// If votes on this proposal are weighted quadratically. Quorum
+    /// is always measured in staked tokens.
+    #[serde(default)]
+    pub quadratic: bool,
+
+    /// When the proposal was created.
+    #[serde(default)]
+    pub created: BlockStamp,
+    /// When the proposal stopped taking votes because it passed,
+    /// failed, or was cancelled or withdrawn.
+    #[serde(default)]
+    pub completed: Option<BlockStamp>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Ballot {
+    pub voter: Addr,
+    pub amount: Uint128,
+    /// Choice indexes in order of preference.
+    pub ranking: Vec<u32>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DelegatedVote {
+    /// The address that cast the vote.
+    pub delegate: Addr,
+    /// The token holder whose voting power was used.
+    pub delegator: Addr,
+    pub position: VotePosition,
+    /// The delegator's balance at the time that the delegate voted,
+    /// reduced if they have since spent or moved it. No tokens are
+    /// locked for a delegated vote so this is never refunded.
+    pub power: Uint128,
+}
+
+/// A request for tokens from the treasury that is decided by
+/// conviction voting rather than by a quorum.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct GrantProposal {
+    pub title: String,
+    pub body: String,
+    pub proposer: Addr,
+    pub recipient: Addr,
+    pub amount: Uint128,
+
+    /// Either Pending or Passed. Grants never fail, they just sit
+    /// without enough conviction.
+    pub status: ProposalStatus,
+    /// The number of tokens currently staked on the grant.
+    pub staked: Uint128,
+    /// The grant's conviction as of `last_update`.
+    pub conviction: Uint128,
+    /// The block height that conviction was last computed at.
+    pub last_update: u64,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
@@ -46,17 +189,83 @@ pub struct TokenInfo {
     pub symbol: String,
     pub decimals: u8,
     pub total_supply: Uint128,
+    pub mode: TokenMode,
 }
 
 pub const STATE: Item<State> = Item::new("state");
-pub const PROPOSALS: Item<Vec<Proposal>> = Item::new("proposals");
+/// The number of proposals that have been created. This is the ID
+/// of the next proposal.
+pub const PROPOSAL_COUNT: Item<u64> = Item::new("proposal_count");
 pub const BALANCES: Map<&Addr, Uint128> = Map::new("balances");
+/// Tokens locked on proposals keyed by (staker, proposal ID). This
+/// includes both votes and proposal deposits.
+pub const STAKED: Map<(&Addr, U64Key), Uint128> = Map::new("staked");
+/// The proposals that an address has staked votes on keyed by
+/// (voter, proposal ID).
+pub const VOTERS: Map<(&Addr, U64Key), Empty> = Map::new("voters");
+pub const GRANTS: Item<Vec<GrantProposal>> = Item::new("grants");
+/// Tokens staked on grant proposals keyed by (staker, grant ID).
+pub const CONVICTION_STAKED: Map<(&Addr, U64Key), Uint128> = Map::new("conviction_staked");
+/// Native coins held as proposal deposits keyed by denom. These are
+/// not part of the DAO's treasury.
+pub const NATIVE_DEPOSITS: Map<&str, Uint128> = Map::new("native_deposits");
+/// CW20 tokens held by the treasury that are paid out on rage quit.
+pub const CW20_TOKENS: Item<Vec<Addr>> = Item::new("cw20_tokens");
+/// Set while rage quit payouts are being sent.
+pub const RAGE_QUIT_LOCK: Item<bool> = Item::new("rage_quit_lock");
 pub const TOKEN_INFO: Item<TokenInfo> = Item::new("token_info");
 pub const ITEMS: Item<Vec<DaoItem>> = Item::new("dao_items");
+pub const VESTING: Map<&Addr, Vec<VestingSchedule>> = Map::new("vesting");
+/// Maps a token holder to the address they have delegated their
+/// voting power to.
+pub const DELEGATES: Map<&Addr, Addr> = Map::new("delegates");
+/// Keyed by (delegate, delegator). Used to find all of the holders
+/// that have delegated to a particular address.
+pub const DELEGATORS: Map<(&Addr, &Addr), Empty> = Map::new("delegators");
+
+pub struct ProposalIndexes<'a> {
+    /// Keyed by the status's name so that queued proposals share a
+    /// key whatever their `executable_at`.
+    pub status: MultiIndex<'a, (Vec<u8>, Vec<u8>), Proposal>,
+    pub proposer: MultiIndex<'a, (Vec<u8>, Vec<u8>), Proposal>,
+}
+
+impl<'a> IndexList<Proposal> for ProposalIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Proposal>> + '_> {
+        let v: Vec<&dyn Index<Proposal>> = vec![&self.status, &self.proposer];
+        Box::new(v.into_iter())
+    }
+}
+
+/// Proposals keyed by ID.
+pub fn proposals<'a>() -> IndexedMap<'a, U64Key, Proposal, ProposalIndexes<'a>> {
+    let indexes = ProposalIndexes {
+        status: MultiIndex::new(
+            |p, pk| (status_name(&p.status).as_bytes().to_vec(), pk),
+            "proposals",
+            "proposals__status",
+        ),
+        proposer: MultiIndex::new(
+            |p, pk| (p.proposer.as_bytes().to_vec(), pk),
+            "proposals",
+            "proposals__proposer",
+        ),
+    };
+    IndexedMap::new("proposals", indexes)
+}
 
 impl Proposal {
-    pub fn new(msg: ProposeMsg, proposer: Addr, proposal_cost: Uint128) -> Self {
+    pub fn new(
+        id: u64,
+        msg: ProposeMsg,
+        proposer: Addr,
+        proposal_cost: Uint128,
+        native_deposit: Option<Coin>,
+        quadratic: bool,
+        block: &BlockInfo,
+    ) -> Self {
         Self {
+            id,
             title: msg.title,
             body: msg.body,
             action: msg.action,
@@ -64,8 +273,17 @@ impl Proposal {
             yes: vec![],
             no: vec![],
             abstain: vec![],
+            no_with_veto: vec![],
+            ballots: vec![],
+            sponsors: vec![],
+            winner: None,
+            delegated: vec![],
             proposer,
             proposal_cost,
+            native_deposit,
+            quadratic,
+            created: block.into(),
+            completed: None,
         }
     }
 
@@ -74,7 +292,88 @@ impl Proposal {
             VotePosition::Yes => self.yes.push((addr.clone(), staked)),
             VotePosition::No => self.no.push((addr.clone(), staked)),
             VotePosition::Abstain => self.abstain.push((addr.clone(), staked)),
+            VotePosition::NoWithVeto => self.no_with_veto.push((addr.clone(), staked)),
+        }
+    }
+
+    /// Zeroes the votes that `addr` has staked on this proposal,
+    /// drops the votes they cast as a delegate, and returns the
+    /// number of tokens they had staked.
+    pub fn remove_votes(&mut self, addr: &Addr) -> Uint128 {
+        let mut removed = Uint128::zero();
+        for (voter, amount) in self
+            .yes
+            .iter_mut()
+            .chain(self.no.iter_mut())
+            .chain(self.abstain.iter_mut())
+            .chain(self.no_with_veto.iter_mut())
+        {
+            if voter == addr {
+                removed += *amount;
+                *amount = Uint128::zero();
+            }
         }
+        for ballot in self.ballots.iter_mut() {
+            if ballot.voter == *addr {
+                ballot.amount = Uint128::zero();
+            }
+        }
+        self.delegated.retain(|d| d.delegate != *addr);
+        removed
+    }
+
+    /// Returns true if `addr` has staked tokens on this proposal
+    /// themselves. Votes that have been withdrawn don't count.
+    pub fn has_direct_vote(&self, addr: &Addr) -> bool {
+        self.direct_votes()
+            .any(|(voter, amount)| voter == addr && !amount.is_zero())
+    }
+
+    /// The number of tokens that `addr` has staked on each position.
+    pub fn votes_of(&self, addr: &Addr) -> Vec<(VotePosition, Uint128)> {
+        let staked = |votes: &[(Addr, Uint128)]| -> Uint128 {
+            votes
+                .iter()
+                .filter(|(voter, _)| voter == addr)
+                .map(|(_, amount)| *amount)
+                .sum()
+        };
+        vec![
+            (VotePosition::Yes, staked(&self.yes)),
+            (VotePosition::No, staked(&self.no)),
+            (VotePosition::Abstain, staked(&self.abstain)),
+            (VotePosition::NoWithVeto, staked(&self.no_with_veto)),
+        ]
+        .into_iter()
+        .filter(|(_, staked)| !staked.is_zero())
+        .collect()
+    }
+
+    /// Iterates over the votes that token holders have staked on
+    /// this proposal themselves.
+    pub fn direct_votes(&self) -> impl Iterator<Item = &(Addr, Uint128)> {
+        self.yes
+            .iter()
+            .chain(self.no.iter())
+            .chain(self.abstain.iter())
+            .chain(self.no_with_veto.iter())
+    }
+
+    /// Returns true if a delegate has already voted with `delegator`'s
+    /// voting power.
+    pub fn has_delegated_vote(&self, delegator: &Addr) -> bool {
+        self.delegated.iter().any(|d| d.delegator == *delegator)
+    }
+
+    pub fn add_delegated_vote(&mut self, vote: DelegatedVote) {
+        self.delegated.push(vote)
+    }
+
+    /// Removes any vote cast on `delegator`'s behalf. Called when a
+    /// delegator votes directly as their vote overrides their
+    /// delegate's.
+    pub fn remove_delegated_votes(&mut self, delegator: &Addr) {
+        self.delegated.retain(|d| d.delegator != *delegator)
     }
 
     fn pos_sum(items: &[(Addr, Uint128)]) -> Uint128 {
@@ -82,16 +381,52 @@ impl Proposal {
     }
 
     pub fn get_votes(&self, position: VotePosition) -> Uint128 {
-        Self::pos_sum(match position {
-            VotePosition::Yes => self.yes.as_slice(),
-            VotePosition::No => self.no.as_slice(),
-            VotePosition::Abstain => self.abstain.as_slice(),
-        })
+        let delegated: Uint128 = self
+            .delegated
+            .iter()
+            .filter(|d| d.position == position)
+            .map(|d| d.power)
+            .sum();
+        delegated
+            + Self::pos_sum(match position {
+                VotePosition::Yes => self.yes.as_slice(),
+                VotePosition::No => self.no.as_slice(),
+                VotePosition::Abstain => self.abstain.as_slice(),
+                VotePosition::NoWithVeto => self.no_with_veto.as_slice(),
+            })
     }
 
     pub fn get_total_votes(&self) -> Uint128 {
         self.get_votes(VotePosition::Yes)
             + self.get_votes(VotePosition::Abstain)
             + self.get_votes(VotePosition::No)
+            + self.get_votes(VotePosition::NoWithVeto)
+    }
+
+    /// The weight of the votes on `position` for deciding the
+    /// outcome of the proposal. This is the number of tokens staked
+    /// unless the proposal is quadratic.
+    pub fn get_weighted_votes(&self, position: VotePosition) -> StdResult<Uint128> {
+        if self.quadratic {
+            quadratic_votes(self, position)
+        } else {
+            Ok(self.get_votes(position))
+        }
+    }
+
+    /// Returns true if the fraction of the vote that is a veto is
+    /// greater than `threshold`.
+    pub fn is_vetoed(&self, threshold: Option<Decimal>) -> StdResult<bool> {
+        let threshold = match threshold {
+            Some(threshold) => threshold,
+            None => return Ok(false),
+        };
+        let veto = self.get_weighted_votes(VotePosition::NoWithVeto)?;
+        let total = [VotePosition::Yes, VotePosition::No, VotePosition::Abstain]
+            .iter()
+            .try_fold(veto, |sum, p| -> StdResult<_> {
+                Ok(sum.checked_add(self.get_weighted_votes(p.clone())?)?)
+            })?;
+        Ok(!total.is_zero() && Decimal::from_ratio(veto, total) > threshold)
     }
 }
@@ -1,9 +1,14 @@
 use cosmwasm_std::Response;
-use cosmwasm_std::{Binary, Deps, DepsMut, Env, MessageInfo, StdError, StdResult, Uint128};
+use cosmwasm_std::{
+    Addr, Binary, Deps, DepsMut, Env, MessageInfo, Order, StdError, StdResult, Storage, Uint128,
+};
 use cw20::{BalanceResponse, Cw20Coin, Cw20ReceiveMsg, TokenInfoResponse};
+use cw_storage_plus::U64Key;
 
-use crate::msg::TokenInstantiateInfo;
-use crate::state::{BALANCES, TOKEN_INFO};
+use crate::msg::{TokenInstantiateInfo, TokenMode, VestingGrant};
+use crate::pause;
+use crate::state::{BALANCES, CONVICTION_STAKED, STAKED, TOKEN_INFO};
+use crate::vesting;
 use crate::ContractError;
 
 impl TokenInstantiateInfo {
@@ -25,19 +30,27 @@ impl TokenInstantiateInfo {
     }
 }
 
-pub fn create_accounts(deps: &mut DepsMut, accounts: &[Cw20Coin]) -> StdResult<Uint128> {
+pub fn create_accounts(
+    deps: &mut DepsMut,
+    accounts: &[Cw20Coin],
+    vesting: &[VestingGrant],
+) -> Result<Uint128, ContractError> {
     let mut total_supply = Uint128::zero();
     for row in accounts {
         let address = deps.api.addr_validate(&row.address)?;
         BALANCES.save(deps.storage, &address, &row.amount)?;
         total_supply += row.amount;
     }
+    for grant in vesting {
+        let address = deps.api.addr_validate(&grant.address)?;
+        total_supply += vesting::create_vesting(deps, &address, grant.schedule.clone())?;
+    }
     Ok(total_supply)
 }
 
 pub fn execute_transfer(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     recipient: String,
     amount: Uint128,
@@ -47,6 +60,9 @@ pub fn execute_transfer(
     }
 
     let rcpt_addr = deps.api.addr_validate(&recipient)?;
+    pause::assert_transfers_not_paused(deps.storage, &env, &info.sender)?;
+    assert_transferable(deps.storage, &env, &info.sender, &rcpt_addr)?;
+    vesting::assert_unlocked(deps.storage, &env.block, &info.sender, amount)?;
 
     BALANCES.update(
         deps.storage,
@@ -69,15 +85,40 @@ pub fn execute_transfer(
     Ok(res)
 }
 
+/// Errors if the token's mode forbids moving tokens from `from` to
+/// `to`. Moves to and from this contract are always allowed so that
+/// votes can be staked and refunded.
+fn assert_transferable(
+    storage: &dyn Storage,
+    env: &Env,
+    from: &Addr,
+    to: &Addr,
+) -> Result<(), ContractError> {
+    if *from == env.contract.address || *to == env.contract.address {
+        return Ok(());
+    }
+    match TOKEN_INFO.load(storage)?.mode {
+        TokenMode::Transferable => Ok(()),
+        TokenMode::NonTransferable | TokenMode::Paused => Err(ContractError::TransfersDisabled),
+    }
+}
+
 pub fn execute_burn(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     amount: Uint128,
 ) -> Result<Response, ContractError> {
     if amount == Uint128::zero() {
         return Err(ContractError::InvalidZeroAmount {});
     }
+    if info.sender != env.contract.address
+        && TOKEN_INFO.load(deps.storage)?.mode == TokenMode::Paused
+    {
+        return Err(ContractError::TransfersDisabled);
+    }
+    pause::assert_transfers_not_paused(deps.storage, &env, &info.sender)?;
+    vesting::assert_unlocked(deps.storage, &env.block, &info.sender, amount)?;
 
     // lower balance
     BALANCES.update(
@@ -102,7 +143,7 @@ pub fn execute_burn(
 
 pub fn execute_send(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     contract: String,
     amount: Uint128,
@@ -111,8 +152,10 @@ pub fn execute_send(
     if amount == Uint128::zero() {
         return Err(ContractError::InvalidZeroAmount {});
     }
-
     let rcpt_addr = deps.api.addr_validate(&contract)?;
+    pause::assert_transfers_not_paused(deps.storage, &env, &info.sender)?;
+    assert_transferable(deps.storage, &env, &info.sender, &rcpt_addr)?;
+    vesting::assert_unlocked(deps.storage, &env.block, &info.sender, amount)?;
 
     // move the tokens to the contract
     BALANCES.update(
@@ -144,6 +187,126 @@ pub fn execute_send(
     Ok(res)
 }
 
+/// Locks `amount` of `address`' tokens on a proposal. Staked tokens
+/// are held separately from the contract's own balance so that they
+/// can never be spent by the DAO.
+pub(crate) fn stake(
+    storage: &mut dyn Storage,
+    address: &Addr,
+    proposal_id: u64,
+    amount: Uint128,
+) -> Result<(), ContractError> {
+    if amount == Uint128::zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+    BALANCES.update(
+        storage,
+        address,
+        |balance: Option<Uint128>| -> StdResult<_> {
+            Ok(balance.unwrap_or_default().checked_sub(amount)?)
+        },
+    )?;
+    STAKED.update(
+        storage,
+        (address, U64Key::new(proposal_id)),
+        |staked: Option<Uint128>| -> StdResult<_> {
+            Ok(staked.unwrap_or_default().checked_add(amount)?)
+        },
+    )?;
+    Ok(())
+}
+
+/// Returns `amount` tokens that `address` has staked on a proposal
+/// to their balance.
+pub(crate) fn unstake(
+    storage: &mut dyn Storage,
+    address: &Addr,
+    proposal_id: u64,
+    amount: Uint128,
+) -> StdResult<()> {
+    if amount.is_zero() {
+        return Ok(());
+    }
+    release_stake(storage, address, proposal_id, amount)?;
+    credit(storage, address, amount)
+}
+
+/// Returns the tokens that `address` has staked on several
+/// proposals to their balance in a single update. `stakes` are
+/// (proposal ID, amount) pairs. Returns the total returned.
+pub(crate) fn unstake_many(
+    storage: &mut dyn Storage,
+    address: &Addr,
+    stakes: &[(u64, Uint128)],
+) -> StdResult<Uint128> {
+    let mut total = Uint128::zero();
+    for (proposal_id, amount) in stakes {
+        release_stake(storage, address, *proposal_id, *amount)?;
+        total = total.checked_add(*amount)?;
+    }
+    credit(storage, address, total)?;
+    Ok(total)
+}
+
+/// Removes `amount` from the tokens `address` has staked on a
+/// proposal without returning them to their balance.
+fn release_stake(
+    storage: &mut dyn Storage,
+    address: &Addr,
+    proposal_id: u64,
+    amount: Uint128,
+) -> StdResult<()> {
+    let key = (address, U64Key::new(proposal_id));
+    let remaining = STAKED
+        .may_load(storage, key.clone())?
+        .unwrap_or_default()
+        .checked_sub(amount)?;
+    if remaining.is_zero() {
+        STAKED.remove(storage, key);
+    } else {
+        STAKED.save(storage, key, &remaining)?;
+    }
+    Ok(())
+}
+
+fn credit(storage: &mut dyn Storage, address: &Addr, amount: Uint128) -> StdResult<()> {
+    BALANCES.update(
+        storage,
+        address,
+        |balance: Option<Uint128>| -> StdResult<_> {
+            Ok(balance.unwrap_or_default().checked_add(amount)?)
+        },
+    )?;
+    Ok(())
+}
+
+/// Moves `amount` tokens that `address` has staked on a proposal
+/// into the DAO's treasury.
+pub(crate) fn forfeit_stake(
+    storage: &mut dyn Storage,
+    env: &Env,
+    address: &Addr,
+    proposal_id: u64,
+    amount: Uint128,
+) -> StdResult<()> {
+    unstake(storage, address, proposal_id, amount)?;
+    BALANCES.update(
+        storage,
+        address,
+        |balance: Option<Uint128>| -> StdResult<_> {
+            Ok(balance.unwrap_or_default().checked_sub(amount)?)
+        },
+    )?;
+    BALANCES.update(
+        storage,
+        &env.contract.address,
+        |balance: Option<Uint128>| -> StdResult<_> {
+            Ok(balance.unwrap_or_default().checked_add(amount)?)
+        },
+    )?;
+    Ok(())
+}
+
 pub fn query_balance(deps: Deps, address: String) -> StdResult<BalanceResponse> {
     let address = deps.api.addr_validate(&address)?;
     let balance = BALANCES
@@ -152,6 +315,28 @@ pub fn query_balance(deps: Deps, address: String) -> StdResult<BalanceResponse>
     Ok(BalanceResponse { balance })
 }
 
+pub fn query_staked_balance(deps: Deps, address: String) -> StdResult<BalanceResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let balance = STAKED
+        .prefix(&address)
+        .range(deps.storage, None, None, Order::Ascending)
+        .chain(
+            CONVICTION_STAKED
+                .prefix(&address)
+                .range(deps.storage, None, None, Order::Ascending),
+        )
+        .map(|item| item.map(|(_, staked)| staked))
+        .sum::<StdResult<Uint128>>()?;
+    Ok(BalanceResponse { balance })
+}
+
+pub fn query_treasury_balance(deps: Deps, env: Env) -> StdResult<BalanceResponse> {
+    let balance = BALANCES
+        .may_load(deps.storage, &env.contract.address)?
+        .unwrap_or_default();
+    Ok(BalanceResponse { balance })
+}
+
 pub fn query_token_info(deps: Deps) -> StdResult<TokenInfoResponse> {
     let info = TOKEN_INFO.load(deps.storage)?;
     let res = TokenInfoResponse {
@@ -0,0 +1,201 @@
+use cosmwasm_std::{
+    Addr, BankMsg, Binary, Coin, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Storage,
+    SubMsg, Uint128,
+};
+use cw20::{BalanceResponse, Cw20QueryMsg};
+
+use crate::msg::{Cw20TokensResponse, TreasuryBalancesResponse};
+use crate::state::{BALANCES, CW20_TOKENS, NATIVE_DEPOSITS};
+use crate::tokens;
+use crate::ContractError;
+
+/// Errors if the DAO's treasury holds fewer than `amount` tokens.
+/// Tokens staked on proposals are not part of the treasury so they
+/// can never be spent this way.
+fn assert_available(
+    storage: &dyn Storage,
+    env: &Env,
+    amount: Uint128,
+) -> Result<(), ContractError> {
+    let available = BALANCES
+        .may_load(storage, &env.contract.address)?
+        .unwrap_or_default();
+    if available < amount {
+        return Err(ContractError::InsufficientTreasury {
+            needed: amount,
+            available,
+        });
+    }
+    Ok(())
+}
+
+fn treasury_info(env: &Env) -> MessageInfo {
+    MessageInfo {
+        sender: env.contract.address.clone(),
+        funds: vec![],
+    }
+}
+
+/// Transfers tokens from the DAO's treasury to `recipient`.
+pub(crate) fn transfer(
+    deps: DepsMut,
+    env: Env,
+    recipient: String,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    assert_available(deps.storage, &env, amount)?;
+    let info = treasury_info(&env);
+    tokens::execute_transfer(deps, env, info, recipient, amount)
+}
+
+/// Sends tokens from the DAO's treasury to `contract` and triggers
+/// an action on the receiving contract.
+pub(crate) fn send(
+    deps: DepsMut,
+    env: Env,
+    contract: String,
+    amount: Uint128,
+    msg: Binary,
+) -> Result<Response, ContractError> {
+    assert_available(deps.storage, &env, amount)?;
+    let info = treasury_info(&env);
+    tokens::execute_send(deps, env, info, contract, amount, msg)
+}
+
+/// Marks native coins held by the contract as a proposal deposit so
+/// that they are not counted as part of the treasury.
+pub(crate) fn lock_native_deposit(storage: &mut dyn Storage, coin: &Coin) -> StdResult<()> {
+    NATIVE_DEPOSITS.update(
+        storage,
+        &coin.denom,
+        |locked: Option<Uint128>| -> StdResult<_> {
+            Ok(locked.unwrap_or_default().checked_add(coin.amount)?)
+        },
+    )?;
+    Ok(())
+}
+
+/// Releases a native proposal deposit. Once released the coins are
+/// part of the treasury.
+pub(crate) fn unlock_native_deposit(storage: &mut dyn Storage, coin: &Coin) -> StdResult<()> {
+    NATIVE_DEPOSITS.update(
+        storage,
+        &coin.denom,
+        |locked: Option<Uint128>| -> StdResult<_> {
+            Ok(locked.unwrap_or_default().checked_sub(coin.amount)?)
+        },
+    )?;
+    Ok(())
+}
+
+/// Releases a native proposal deposit and returns the message that
+/// sends it back to `to`.
+pub(crate) fn refund_native_deposit(
+    storage: &mut dyn Storage,
+    to: &str,
+    coin: &Coin,
+) -> StdResult<SubMsg> {
+    unlock_native_deposit(storage, coin)?;
+    Ok(SubMsg::new(BankMsg::Send {
+        to_address: to.to_string(),
+        amount: vec![coin.clone()],
+    }))
+}
+
+/// The native coins held by the contract less any that are locked as
+/// proposal deposits.
+pub fn native_balances(deps: Deps, env: &Env) -> StdResult<Vec<Coin>> {
+    let mut balances = deps
+        .querier
+        .query_all_balances(env.contract.address.as_str())?;
+    for coin in balances.iter_mut() {
+        let locked = NATIVE_DEPOSITS
+            .may_load(deps.storage, &coin.denom)?
+            .unwrap_or_default();
+        coin.amount = coin.amount.saturating_sub(locked);
+    }
+    balances.retain(|c| !c.amount.is_zero());
+    Ok(balances)
+}
+
+/// Sends native coins from the DAO's treasury to `to`.
+pub(crate) fn bank_send(
+    deps: Deps,
+    env: &Env,
+    to: String,
+    amount: Vec<Coin>,
+) -> Result<SubMsg, ContractError> {
+    let balances = native_balances(deps, env)?;
+    for needed in &amount {
+        let available = balances
+            .iter()
+            .find(|c| c.denom == needed.denom)
+            .map(|c| c.amount)
+            .unwrap_or_default();
+        if available < needed.amount {
+            return Err(ContractError::InsufficientNativeTreasury {
+                needed: needed.to_string(),
+                available: Coin {
+                    denom: needed.denom.clone(),
+                    amount: available,
+                }
+                .to_string(),
+            });
+        }
+    }
+    Ok(SubMsg::new(BankMsg::Send {
+        to_address: to,
+        amount,
+    }))
+}
+
+/// Adds and removes tokens from the list of CW20 tokens paid out on
+/// rage quit.
+pub(crate) fn update_cw20_tokens(
+    deps: DepsMut,
+    add: &[String],
+    remove: &[String],
+) -> StdResult<()> {
+    let mut tokens = CW20_TOKENS.may_load(deps.storage)?.unwrap_or_default();
+    for token in add {
+        let token = deps.api.addr_validate(token)?;
+        if !tokens.contains(&token) {
+            tokens.push(token);
+        }
+    }
+    for token in remove {
+        let token = deps.api.addr_validate(token)?;
+        tokens.retain(|t| *t != token);
+    }
+    CW20_TOKENS.save(deps.storage, &tokens)
+}
+
+/// The contract's balance of each registered CW20 token.
+pub fn cw20_balances(deps: Deps, env: &Env) -> StdResult<Vec<(Addr, Uint128)>> {
+    CW20_TOKENS
+        .may_load(deps.storage)?
+        .unwrap_or_default()
+        .into_iter()
+        .map(|token| {
+            let res: BalanceResponse = deps.querier.query_wasm_smart(
+                token.as_str(),
+                &Cw20QueryMsg::Balance {
+                    address: env.contract.address.to_string(),
+                },
+            )?;
+            Ok((token, res.balance))
+        })
+        .collect()
+}
+
+pub fn query_cw20_tokens(deps: Deps) -> StdResult<Cw20TokensResponse> {
+    Ok(Cw20TokensResponse {
+        tokens: CW20_TOKENS.may_load(deps.storage)?.unwrap_or_default(),
+    })
+}
+
+pub fn query_treasury_balances(deps: Deps, env: Env) -> StdResult<TreasuryBalancesResponse> {
+    Ok(TreasuryBalancesResponse {
+        balances: native_balances(deps, &env)?,
+    })
+}
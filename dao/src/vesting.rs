@@ -0,0 +1,145 @@
+use cosmwasm_std::{Addr, BlockInfo, Deps, DepsMut, StdResult, Storage, Uint128};
+
+use crate::msg::{VestingInfoResponse, VestingSchedule, VestingUnit};
+use crate::state::{BALANCES, TOKEN_INFO, VESTING};
+use crate::ContractError;
+
+impl VestingSchedule {
+    pub fn validate(&self) -> Result<(), ContractError> {
+        if self.amount.is_zero() {
+            return Err(ContractError::InvalidVestingSchedule {
+                reason: "amount must be greater than zero".to_string(),
+            });
+        }
+        if self.start > self.cliff || self.cliff > self.end {
+            return Err(ContractError::InvalidVestingSchedule {
+                reason: "expected start <= cliff <= end".to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// The number of tokens that have been released as of `block`.
+    pub fn vested(&self, block: &BlockInfo) -> Uint128 {
+        let now = match self.unit {
+            VestingUnit::Height => block.height,
+            VestingUnit::Time => block.time.seconds(),
+        };
+        if now < self.cliff {
+            Uint128::zero()
+        } else if now >= self.end {
+            self.amount
+        } else {
+            // Start < end here as start <= cliff <= now < end.
+            self.amount
+                .multiply_ratio(now - self.start, self.end - self.start)
+        }
+    }
+
+    /// The number of tokens that have yet to be released as of
+    /// `block`.
+    pub fn locked(&self, block: &BlockInfo) -> Uint128 {
+        self.amount - self.vested(block)
+    }
+}
+
+/// Mints `schedule.amount` new tokens to `address` and locks them
+/// according to `schedule`.
+pub(crate) fn create_vesting(
+    deps: &mut DepsMut,
+    address: &Addr,
+    schedule: VestingSchedule,
+) -> Result<Uint128, ContractError> {
+    schedule.validate()?;
+    let amount = schedule.amount;
+
+    BALANCES.update(
+        deps.storage,
+        address,
+        |balance: Option<Uint128>| -> StdResult<_> {
+            Ok(balance.unwrap_or_default().checked_add(amount)?)
+        },
+    )?;
+    VESTING.update(
+        deps.storage,
+        address,
+        |schedules: Option<Vec<VestingSchedule>>| -> StdResult<_> {
+            let mut schedules = schedules.unwrap_or_default();
+            schedules.push(schedule);
+            Ok(schedules)
+        },
+    )?;
+    Ok(amount)
+}
+
+/// Mints tokens for a vesting schedule after instantiation. Unlike
+/// `create_vesting` this also updates the total supply.
+pub(crate) fn grant_vesting(
+    mut deps: DepsMut,
+    address: &Addr,
+    schedule: VestingSchedule,
+) -> Result<(), ContractError> {
+    let amount = create_vesting(&mut deps, address, schedule)?;
+    TOKEN_INFO.update(deps.storage, |mut info| -> StdResult<_> {
+        info.total_supply = info.total_supply.checked_add(amount)?;
+        Ok(info)
+    })?;
+    Ok(())
+}
+
+/// The number of tokens held by `address` that have yet to vest.
+pub fn locked_balance(
+    storage: &dyn Storage,
+    block: &BlockInfo,
+    address: &Addr,
+) -> StdResult<Uint128> {
+    Ok(VESTING
+        .may_load(storage, address)?
+        .unwrap_or_default()
+        .iter()
+        .map(|s| s.locked(block))
+        .sum())
+}
+
+/// Errors if spending `amount` of `address`' tokens would spend
+/// tokens that have yet to vest.
+pub(crate) fn assert_unlocked(
+    storage: &dyn Storage,
+    block: &BlockInfo,
+    address: &Addr,
+    amount: Uint128,
+) -> Result<(), ContractError> {
+    let locked = locked_balance(storage, block, address)?;
+    if locked.is_zero() {
+        return Ok(());
+    }
+    let available = BALANCES
+        .may_load(storage, address)?
+        .unwrap_or_default()
+        .saturating_sub(locked);
+    if available < amount {
+        return Err(ContractError::TokensLocked {
+            available,
+            needed: amount,
+        });
+    }
+    Ok(())
+}
+
+pub fn query_vesting_info(
+    deps: Deps,
+    block: &BlockInfo,
+    address: String,
+) -> StdResult<VestingInfoResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let schedules = VESTING
+        .may_load(deps.storage, &address)?
+        .unwrap_or_default();
+    let vested = schedules.iter().map(|s| s.vested(block)).sum();
+    let locked = schedules.iter().map(|s| s.locked(block)).sum();
+    Ok(VestingInfoResponse {
+        schedules,
+        vested,
+        locked,
+    })
+}
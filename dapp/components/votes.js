@@ -1,30 +1,24 @@
-import { Card, Heading, Flex, Text } from 'theme-ui'
+import { Card, Heading } from 'theme-ui'
 
 function Vote(props) {
     return (
-	<Card mt={2} mb={2} key={props.addr + props.pos + props.amount} p={2} bg={props.pos}>
+	<Card mt={2} mb={2} key={props.pos} p={2} bg={props.bg}>
 	    <Heading>{props.pos}</Heading>
 	    <Heading as='p'>tokens: {props.amount}</Heading>
-	    <Text>address: {props.addr}</Text>
 	</Card>
     )
 }
 
 export default function Votes(props) {
-    if (!props.yes || !props.no || !props.abstain) {
+    if (!props.tally) {
 	return null
     }
     return (
 	<>
-	    {props.yes.map((vote) => {
-		return (<Vote amount={vote[1]} addr={vote[0]} pos='yes'/>)
-	    })}
-	    {props.no.map((vote) => {
-		return (<Vote amount={vote[1]} addr={vote[0]} pos='no'/>)
-	    })}
-	    {props.abstain.map((vote) => {
-		return (<Vote amount={vote[1]} addr={vote[0]} pos='abstain'/>)
-	    })}
+	    <Vote amount={props.tally.yes} pos='yes' bg='yes'/>
+	    <Vote amount={props.tally.no} pos='no' bg='no'/>
+	    <Vote amount={props.tally.abstain} pos='abstain' bg='abstain'/>
+	    <Vote amount={props.tally.no_with_veto} pos='no with veto' bg='no'/>
 	</>
     )
 }
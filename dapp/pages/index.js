@@ -11,14 +11,14 @@ let loadingItems = true
 
 const propGetter = async (setProps) => {
     const client = await getClient()
-    const props = await client.queryContractSmart('juno1skuakpnx8gec0avqdr00nl5339k5nc6rplsnr4gtv3ge3q8e2l2qwayda3', "list_proposals")
+    const { proposals } = await client.queryContractSmart('juno1skuakpnx8gec0avqdr00nl5339k5nc6rplsnr4gtv3ge3q8e2l2qwayda3', "list_proposals")
     loadingProps = false
-    setProps(props)
+    setProps(proposals)
 }
 
 const itemGetter = async (setItems) => {
     const client = await getClient()
-    const items = await client.queryContractSmart('juno1skuakpnx8gec0avqdr00nl5339k5nc6rplsnr4gtv3ge3q8e2l2qwayda3', "list_items")
+    const { items } = await client.queryContractSmart('juno1skuakpnx8gec0avqdr00nl5339k5nc6rplsnr4gtv3ge3q8e2l2qwayda3', "list_items")
     loadingItems = false
     setItems(items)
 }
@@ -45,13 +45,13 @@ export default function Home() {
 	    })}
 
 	    <Heading as="h2">Proposals</Heading>
-	    {loadingProps ? <Spinner/> : props.map((prop, idx) => {
+	    {loadingProps ? <Spinner/> : props.map((prop) => {
 		return (
 		    <Prop
 			title={prop.title}
 			status={prop.status}
 			body={prop.body}
-			id={idx}
+			id={prop.id}
 		    />
 		)
 	    })}
@@ -11,6 +11,9 @@ import { Spinner, Heading, Paragraph, Message, Container } from 'theme-ui'
 
 let loading = true
 
+// Queued proposals have a status like `{"Queued": {...}}`.
+const statusName = (status) => typeof status === 'object' ? Object.keys(status)[0] : status
+
 const getProp = async (id, setProp) => {
     const client = await getClient()
     const prop = await client.queryContractSmart('juno1skuakpnx8gec0avqdr00nl5339k5nc6rplsnr4gtv3ge3q8e2l2qwayda3', {"get_proposal": { "proposal_id": parseInt(id)}})
@@ -49,7 +52,7 @@ export default function Proposal() {
 		<Heading as='h3' sx={{mt: 2, mr: 1}}>proposal {id}</Heading>
 	    </Flex>
 	    <Box mt={2} p={1} bg="highlight">
-		{prop.status}
+		{statusName(prop.status)}
 	    </Box>
 	    <Heading variant="text.display">
 		{prop ? prop.title : "Proposal {id}"}
@@ -62,7 +65,7 @@ export default function Proposal() {
 		{prop.action ? JSON.stringify(prop.action) : ""}
 	    </Box>
 	    <Heading variant="text.heading" mt={2}>votes</Heading>
-	    <Votes yes={prop.yes} no={prop.no} abstain={prop.abstain}/>
+	    <Votes tally={prop.tally}/>
 	</Layout>
     )
 }